@@ -1,6 +1,17 @@
-use clap::{Parser, Subcommand};
-use nalgebra::{Isometry3, Matrix3, Rotation3, Translation3, Unit, Vector3};
-use std::io::{self, BufRead};
+use clap::{Parser, Subcommand, ValueEnum};
+use nalgebra::{
+	DMatrix, Isometry3, Matrix3, Matrix4, Point3, Quaternion, Rotation3, Translation3, Unit,
+	UnitQuaternion, Vector2, Vector3, Vector4,
+};
+use std::cell::{Cell, RefCell};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, LineWriter, Write};
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 #[derive(Clone, Copy)]
 struct Line {
@@ -8,63 +19,994 @@ struct Line {
 	heading: Vector3<f32>,
 }
 
-fn parse_vector3(s: &str) -> Result<Vector3<f32>, &'static str> {
+/// Splits on whitespace, or when lenient on whitespace and commas
+fn split_words(s: &str, lenient: bool) -> Vec<&str> {
+	if lenient && s.contains(',') {
+		s.split(|c: char| c.is_whitespace() || c == ',')
+			.filter(|word| !word.is_empty())
+			.collect()
+	} else {
+		s.split_whitespace().collect()
+	}
+}
+
+/// Fallback for --lenient-parse accepting commas, whitespace or both between coordinates
+fn parse_vector3_lenient(s: &str, lenient: bool) -> Option<Vector3<f32>> {
+	if !lenient {
+		return None;
+	}
+	match split_words(s, true).as_slice() {
+		[x, y, z] => Some(Vector3::new(
+			x.parse().ok()?,
+			y.parse().ok()?,
+			z.parse().ok()?,
+		)),
+		_ => None,
+	}
+}
+
+fn parse_vector3(s: &str, lenient: bool) -> Result<Vector3<f32>, &'static str> {
 	let parts: Vec<&str> = s.split(',').collect();
 	if parts.len() != 3 {
-		return Err("Each vector must have exactly three coordinates");
+		return parse_vector3_lenient(s, lenient)
+			.ok_or("Each vector must have exactly three coordinates");
 	}
 	let coords: Result<Vec<f32>, _> = parts.iter().map(|&num| num.parse::<f32>()).collect();
 	match coords {
 		Ok(coords) if coords.len() == 3 => Ok(Vector3::new(coords[0], coords[1], coords[2])),
-		_ => Err("Invalid vector format"),
+		_ => parse_vector3_lenient(s, lenient).ok_or("Invalid vector format"),
+	}
+}
+
+/// Converts a vector, line or keyframe argument once --lenient-parse is known,
+/// naming the argument in the error
+fn convert<T>(
+	s: &str,
+	lenient: bool,
+	parse: fn(&str, bool) -> Result<T, &'static str>,
+) -> Result<T, String> {
+	parse(s, lenient).map_err(|e| format!("{}: \"{}\"", e, s))
+}
+
+/// Converts every value of a repeated argument as `convert` does
+fn convert_all<T>(
+	values: &[String],
+	lenient: bool,
+	parse: fn(&str, bool) -> Result<T, &'static str>,
+) -> Result<Vec<T>, String> {
+	values.iter().map(|s| convert(s, lenient, parse)).collect()
+}
+
+#[derive(Clone, Copy)]
+struct Keyframe {
+	t: f32,
+	angle: f32,
+	offset: Vector3<f32>,
+}
+
+fn parse_keyframe(s: &str, lenient: bool) -> Result<Keyframe, &'static str> {
+	let words: Vec<&str> = s.split_whitespace().collect();
+	if words.len() != 2 && words.len() != 3 {
+		return Err("Each keyframe must be a position, an angle and optionally an offset vector");
+	}
+	let t = words[0]
+		.parse::<f32>()
+		.ok()
+		.filter(|t| t.is_finite())
+		.ok_or("Invalid keyframe position")?;
+	let angle = words[1]
+		.parse::<f32>()
+		.map_err(|_| "Invalid keyframe angle")?;
+	let offset = match words.get(2) {
+		Some(offset) => parse_vector3(offset, lenient)?,
+		None => Vector3::zeros(),
+	};
+	Ok(Keyframe { t, angle, offset })
+}
+
+/// Parses a rigid transform from a dual quaternion "w,x,y,z,w,x,y,z" with the
+/// real (rotation) part first and the dual part second
+fn parse_dual_quaternion(s: &str) -> Result<Isometry3<f32>, &'static str> {
+	let values: Vec<f32> = s
+		.split(',')
+		.map(|num| num.parse::<f32>())
+		.collect::<Result<_, _>>()
+		.map_err(|_| "Invalid dual quaternion format")?;
+	if values.len() != 8 {
+		return Err("A dual quaternion must have exactly eight values");
+	}
+	let real = Quaternion::new(values[0], values[1], values[2], values[3]);
+	let dual = Quaternion::new(values[4], values[5], values[6], values[7]);
+	let norm = real.norm();
+	if norm == 0.0 {
+		return Err("The real part of a dual quaternion must not be zero");
+	}
+	let (real, dual) = (real / norm, dual / norm);
+	let translation = (dual * real.conjugate() * 2.0).imag();
+	Ok(Isometry3::from_parts(
+		Translation3::from(translation),
+		UnitQuaternion::new_unchecked(real),
+	))
+}
+
+fn parse_counts(s: &str) -> Result<[usize; 3], &'static str> {
+	let counts: Vec<usize> = s
+		.split(',')
+		.map(|num| num.parse::<usize>())
+		.collect::<Result<_, _>>()
+		.map_err(|_| "Invalid count format")?;
+	match counts[..] {
+		[x, y, z] => Ok([x, y, z]),
+		_ => Err("Counts must have exactly three values"),
+	}
+}
+
+/// Length of a unit in meters, either a unit name or a custom number of meters
+fn parse_unit(s: &str) -> Result<f64, &'static str> {
+	match s {
+		"mm" => Ok(0.001),
+		"cm" => Ok(0.01),
+		"m" => Ok(1.0),
+		"inch" | "in" => Ok(0.0254),
+		"foot" | "ft" => Ok(0.3048),
+		_ => match s.parse::<f64>() {
+			Ok(meters) if meters > 0.0 => Ok(meters),
+			_ => Err("Unit must be mm, cm, m, inch, foot or a positive number of meters"),
+		},
+	}
+}
+
+fn parse_distance(s: &str) -> Result<f32, &'static str> {
+	match s.parse::<f32>() {
+		Ok(distance) if distance >= 0.0 => Ok(distance),
+		_ => Err("Distance must be a non negative number"),
 	}
 }
 
-fn parse_line(s: &str) -> Result<Line, &'static str> {
+/// Parses an axis aligned plane such as z=0
+fn parse_plane(s: &str) -> Result<(Axis, f32), &'static str> {
+	let (axis, offset) = s
+		.split_once('=')
+		.ok_or("Plane must be written as axis=offset")?;
+	let axis = Axis::from_str(axis.trim(), true).map_err(|_| "Plane axis must be x, y or z")?;
+	let offset = offset
+		.trim()
+		.parse::<f32>()
+		.map_err(|_| "Invalid plane offset")?;
+	Ok((axis, offset))
+}
+
+fn parse_line(s: &str, lenient: bool) -> Result<Line, &'static str> {
 	let vectors: Vec<&str> = s.split_whitespace().collect();
 	if vectors.len() != 2 {
 		return Err("Each line must be defined by exactly two vectors");
 	}
-	let origin = parse_vector3(vectors[0])?;
-	let heading = parse_vector3(vectors[1])?;
+	let origin = parse_vector3(vectors[0], lenient)?;
+	let heading = parse_vector3(vectors[1], lenient)?;
 	Ok(Line { origin, heading })
 }
 
+#[derive(Clone, Copy, ValueEnum)]
+enum Axis {
+	X,
+	Y,
+	Z,
+}
+
+impl Axis {
+	fn index(self) -> usize {
+		match self {
+			Axis::X => 0,
+			Axis::Y => 1,
+			Axis::Z => 2,
+		}
+	}
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum DccFormat {
+	/// Row major, translation in the last column, Z up
+	Blender,
+	/// Row major, translation in the last row, Y up
+	Maya,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Center {
+	/// Mean of the vertices
+	Centroid,
+	/// Center of the bounding box
+	Box,
+	/// Center of the smallest enclosing sphere
+	Sphere,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Easing {
+	Linear,
+	#[value(alias = "easeIn")]
+	EaseIn,
+	#[value(alias = "easeOut")]
+	EaseOut,
+	#[value(alias = "easeInOut")]
+	EaseInOut,
+}
+
+impl Easing {
+	fn apply(self, t: f32) -> f32 {
+		match self {
+			Easing::Linear => t,
+			Easing::EaseIn => t * t,
+			Easing::EaseOut => 1.0 - (1.0 - t) * (1.0 - t),
+			Easing::EaseInOut if t < 0.5 => 2.0 * t * t,
+			Easing::EaseInOut => 1.0 - (2.0 - 2.0 * t).powi(2) / 2.0,
+		}
+	}
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum XyzColumn {
+	X,
+	Y,
+	Z,
+	Nx,
+	Ny,
+	Nz,
+	Other,
+}
+
+#[derive(Clone)]
+struct XyzLayout {
+	columns: Vec<XyzColumn>,
+}
+
+fn parse_xyz_columns(s: &str) -> Result<XyzLayout, &'static str> {
+	let columns: Vec<XyzColumn> = s
+		.split(',')
+		.map(|name| match name {
+			"x" => XyzColumn::X,
+			"y" => XyzColumn::Y,
+			"z" => XyzColumn::Z,
+			"nx" => XyzColumn::Nx,
+			"ny" => XyzColumn::Ny,
+			"nz" => XyzColumn::Nz,
+			_ => XyzColumn::Other,
+		})
+		.collect();
+	let count = |column| columns.iter().filter(|&&c| c == column).count();
+	if [XyzColumn::X, XyzColumn::Y, XyzColumn::Z]
+		.iter()
+		.any(|&c| count(c) != 1)
+	{
+		return Err("Layout must contain each of x, y and z exactly once");
+	}
+	let normals = [XyzColumn::Nx, XyzColumn::Ny, XyzColumn::Nz].map(count);
+	if normals != [0, 0, 0] && normals != [1, 1, 1] {
+		return Err("Layout must contain all of nx, ny and nz or none of them");
+	}
+	Ok(XyzLayout { columns })
+}
+
 #[derive(Subcommand)]
 enum Commands {
 	/// Translates object
 	Translate {
-		#[clap(allow_hyphen_values = true, value_parser = parse_vector3, value_name="vector", help="vector with comma separated values")]
-		translation: Vector3<f32>,
+		#[clap(
+			allow_hyphen_values = true,
+			value_name = "vector",
+			env = "MESHTRANSFORM_TRANSLATE",
+			help = "vector with comma separated values"
+		)]
+		translation: String,
 	},
 	/// Rotates object
 	Rotate {
-		#[clap(allow_hyphen_values = true, value_parser = parse_vector3, value_name="vector", help="vector with comma separated values")]
-		axis: Vector3<f32>,
-		#[clap(allow_hyphen_values = true)]
+		#[clap(
+			allow_hyphen_values = true,
+			value_name = "vector",
+			env = "MESHTRANSFORM_ROTATE_AXIS",
+			help = "vector with comma separated values"
+		)]
+		axis: String,
+		#[clap(allow_hyphen_values = true, env = "MESHTRANSFORM_ROTATE_ANGLE")]
 		angle: f32,
 	},
 	/// Scales object
 	Scale {
-		#[clap(allow_hyphen_values = true, value_parser = parse_vector3, value_name="vector", help="vector with comma separated values")]
-		scale: Vector3<f32>,
+		#[clap(
+			allow_hyphen_values = true,
+			value_name = "vector",
+			env = "MESHTRANSFORM_SCALE",
+			help = "vector with comma separated values"
+		)]
+		scale: String,
+	},
+	/// Applies the rigid transform of a dual quaternion
+	DualQuat {
+		#[clap(allow_hyphen_values = true, value_parser = parse_dual_quaternion, value_name="dual-quaternion", help="real then dual part as eight comma separated values w,x,y,z,w,x,y,z")]
+		isometry: Isometry3<f32>,
+	},
+	/// Scales object between units of length
+	Units {
+		#[clap(long, value_parser = parse_unit, value_name="unit", help="unit of the input: mm, cm, m, inch, foot or meters per unit")]
+		from: f64,
+		#[clap(long, value_parser = parse_unit, value_name="unit", help="unit of the output: mm, cm, m, inch, foot or meters per unit")]
+		to: f64,
+	},
+	/// Scales object along the axes of a rotated frame
+	ScaleOriented {
+		#[clap(
+			long,
+			allow_hyphen_values = true,
+			value_name = "vector",
+			help = "rotation axis of the frame"
+		)]
+		axis: String,
+		#[clap(long, allow_hyphen_values = true, help = "rotation angle of the frame")]
+		angle: f32,
+		#[clap(
+			long,
+			allow_hyphen_values = true,
+			value_name = "vector",
+			help = "scale along each axis of the frame"
+		)]
+		scale: String,
+	},
+	/// Thin plate spline warp through corresponding control points
+	Tps {
+		#[clap(
+			long,
+			value_name = "PATH",
+			help = "control points before the warp, one \"x y z\" or obj vertex per line"
+		)]
+		from_points: PathBuf,
+		#[clap(
+			long,
+			value_name = "PATH",
+			help = "control points after the warp, in the same order"
+		)]
+		to_points: PathBuf,
+	},
+	/// Pinches vertices toward the view axis of a camera the farther they are from it
+	FakeDepth {
+		#[clap(
+			long,
+			allow_hyphen_values = true,
+			value_name = "vector",
+			help = "camera position"
+		)]
+		camera: String,
+		#[clap(
+			long,
+			allow_hyphen_values = true,
+			value_name = "vector",
+			help = "view direction. Defaults to looking at the origin"
+		)]
+		look: Option<String>,
+		#[clap(long, help = "pinch per unit of distance from the camera")]
+		strength: f32,
 	},
 	/// Non linear warp by interpolation
 	Warp {
-		#[clap(long, allow_hyphen_values = true, value_parser = parse_line, long="line", value_name="line", help="Specifies a line with two vectors. Should be used multiple times")]
-		lines: Vec<Line>,
+		#[clap(
+			long,
+			allow_hyphen_values = true,
+			long = "line",
+			value_name = "line",
+			help = "Specifies a line with two vectors. Should be used multiple times"
+		)]
+		lines: Vec<String>,
+		#[clap(
+			long,
+			value_name = "PATH",
+			help = "Reads additional lines from a file, one \"origin heading\" per line"
+		)]
+		lines_file: Option<PathBuf>,
+		#[clap(
+			long,
+			value_name = "angle-eps",
+			help = "Merges lines whose directions are within this angle (radians)"
+		)]
+		merge_collinear: Option<f32>,
+		#[clap(
+			long,
+			allow_hyphen_values = true,
+			value_name = "vector",
+			help = "Prints the jacobian of the warp at a point instead of transforming. May be used multiple times"
+		)]
+		warp_jacobian: Vec<String>,
+		#[clap(
+			long,
+			allow_hyphen_values = true,
+			value_name = "transform",
+			help = "Affine transform applied before the warp, e.g. \"rotate 0,0,1 1.57\""
+		)]
+		pre: Option<String>,
+		#[clap(
+			long,
+			allow_hyphen_values = true,
+			value_name = "transform",
+			help = "Affine transform applied after the warp, e.g. \"translate 0,0,1\""
+		)]
+		post: Option<String>,
+	},
+	/// Displaces vertices by a sine wave travelling along a direction
+	Wave {
+		#[clap(
+			long,
+			allow_hyphen_values = true,
+			value_name = "vector",
+			help = "direction the wave travels in"
+		)]
+		direction: String,
+		#[clap(
+			long,
+			allow_hyphen_values = true,
+			value_name = "vector",
+			default_value = "0,0,1",
+			help = "axis vertices are displaced along"
+		)]
+		displacement: String,
+		#[clap(long, allow_hyphen_values = true)]
+		amplitude: f32,
+		#[clap(long)]
+		wavelength: f32,
+		#[clap(long, allow_hyphen_values = true, default_value_t = 0.0)]
+		phase: f32,
+	},
+	/// Applies the rigid motion between two frames with corresponding vertices
+	Track {
+		#[clap(long, value_name = "PATH", help = "obj or stl of the previous frame")]
+		previous: PathBuf,
+		#[clap(long, value_name = "PATH", help = "obj or stl of the current frame")]
+		current: PathBuf,
+	},
+	/// Rotates and offsets slices along an axis by interpolated keyframes
+	Sweep {
+		#[clap(
+			long,
+			allow_hyphen_values = true,
+			value_name = "vector",
+			help = "axis the keyframes are placed along"
+		)]
+		axis: String,
+		#[clap(
+			long,
+			allow_hyphen_values = true,
+			long = "key",
+			value_name = "keyframe",
+			help = "Specifies a keyframe as \"t angle [offset]\". Should be used multiple times"
+		)]
+		keys: Vec<String>,
+	},
+	/// Rotates the longest face edge to point along a direction
+	EdgeAlign {
+		#[clap(
+			long,
+			allow_hyphen_values = true,
+			value_name = "vector",
+			help = "direction the longest edge is aligned to"
+		)]
+		to: String,
+	},
+	/// Applies a matrix exported from Blender or Maya
+	Matrix {
+		#[clap(
+			long,
+			value_name = "PATH",
+			help = "text file holding the exported 4x4 matrix"
+		)]
+		from_dcc: PathBuf,
+		#[clap(long, value_enum)]
+		format: DccFormat,
+	},
+	/// Offsets vertices with a falloff over edge distance from a seed vertex
+	SurfaceFalloff {
+		#[clap(long, value_name = "N", help = "zero based index of the seed vertex")]
+		seed_vertex: usize,
+		#[clap(long)]
+		radius: f32,
+		#[clap(
+			long,
+			allow_hyphen_values = true,
+			value_name = "vector",
+			help = "offset applied in full at the seed vertex"
+		)]
+		offset: String,
+	},
+	/// Translates object so a vertex sits at the origin
+	SetOrigin {
+		#[clap(
+			long,
+			value_name = "N",
+			help = "zero based index of the vertex moved to the origin"
+		)]
+		vertex: usize,
+	},
+	/// Repeats object on a grid
+	Array {
+		#[clap(long, value_parser = parse_counts, value_name="counts", help="number of copies along x, y and z")]
+		count: [usize; 3],
+		#[clap(
+			long,
+			allow_hyphen_values = true,
+			value_name = "vector",
+			help = "distance between copies along x, y and z"
+		)]
+		spacing: String,
+		#[clap(
+			long,
+			allow_hyphen_values = true,
+			value_name = "vector",
+			default_value = "0,0,1",
+			help = "axis of the rotation added per copy"
+		)]
+		step_axis: String,
+		#[clap(
+			long,
+			allow_hyphen_values = true,
+			default_value_t = 0.0,
+			help = "angle added per copy"
+		)]
+		step_angle: f32,
+	},
+	/// Reduces the face count by quadric error edge collapse
+	Simplify {
+		#[clap(long, help = "fraction of triangles to keep")]
+		ratio: f32,
+	},
+	/// Rotates a measured up direction onto +Z
+	UpCorrect {
+		#[clap(
+			long,
+			allow_hyphen_values = true,
+			value_name = "vector",
+			help = "up direction measured in the scan"
+		)]
+		measured_up: String,
+	},
+	/// Reports planes through the bounding box center the object is mirror symmetric across
+	Symmetry {
+		#[clap(
+			long,
+			allow_hyphen_values = true,
+			long = "plane",
+			value_name = "normal",
+			help = "Adds a candidate plane normal. Axis and diagonal planes are always tested"
+		)]
+		planes: Vec<String>,
+		#[clap(
+			long,
+			default_value_t = 1e-3,
+			help = "distance within which a mirrored vertex must meet another vertex"
+		)]
+		tolerance: f32,
+	},
+	/// Projects object onto an axis aligned plane along light rays
+	Footprint {
+		#[clap(long, allow_hyphen_values = true, value_parser = parse_plane, value_name="plane", default_value="z=0", help="plane to project onto, e.g. z=0")]
+		plane: (Axis, f32),
+		#[clap(
+			long,
+			allow_hyphen_values = true,
+			value_name = "vector",
+			help = "light direction, or position with --point-light. Defaults to straight onto the plane"
+		)]
+		light: Option<String>,
+		#[clap(long, help = "Treats --light as the position of a point light")]
+		point_light: bool,
+		#[clap(
+			long,
+			help = "Outputs only the convex outline of the footprint as a single face"
+		)]
+		outline: bool,
+	},
+	/// Snaps vertices onto nearby axis aligned planes shared by many vertices
+	SnapToPlanes {
+		#[clap(long, help = "distance within which vertices are snapped onto a plane")]
+		eps: f32,
+		#[clap(
+			long,
+			default_value_t = 3,
+			help = "vertices needed near a coordinate for it to be a plane"
+		)]
+		min_vertices: usize,
+	},
+	/// Displaces vertices along their normal by their mean curvature
+	CurvatureDisplace {
+		#[clap(
+			long,
+			allow_hyphen_values = true,
+			help = "displacement per unit of mean curvature"
+		)]
+		amount: f32,
+	},
+	/// Centers object at the origin and scales its farthest vertex to a radius
+	SphereNormalize {
+		#[clap(long, default_value_t = 1.0)]
+		radius: f32,
+		#[clap(long, value_enum, default_value = "centroid")]
+		center: Center,
+	},
+	/// Sags object between its two ends along an axis, like a hanging cable
+	Sag {
+		#[clap(
+			long,
+			value_enum,
+			help = "axis whose lowest and highest vertices stay pinned"
+		)]
+		pinned_axis: Axis,
+		#[clap(
+			long,
+			allow_hyphen_values = true,
+			help = "sag halfway between the pinned ends"
+		)]
+		strength: f32,
+		#[clap(
+			long,
+			allow_hyphen_values = true,
+			value_name = "vector",
+			default_value = "0,0,-1",
+			help = "direction of the sag"
+		)]
+		gravity: String,
+	},
+	/// Writes frames easing object from its original shape to a transformed one
+	Ease {
+		#[clap(
+			long,
+			allow_hyphen_values = true,
+			value_name = "transform",
+			help = "transform reached on the last frame, e.g. \"rotate 0,0,1 3.14\""
+		)]
+		transform: String,
+		#[clap(long, default_value_t = 10)]
+		steps: usize,
+		#[clap(long, value_enum, default_value = "linear")]
+		curve: Easing,
+		#[clap(
+			long,
+			value_name = "PATTERN",
+			default_value = "frame{}.obj",
+			help = "file name of each frame, {} being replaced by the frame number"
+		)]
+		output: String,
+	},
+	/// Rotates object about its centroid so its principal axes lie along a reference frame
+	FrameAlign {
+		#[clap(
+			long,
+			allow_hyphen_values = true,
+			value_name = "vector",
+			help = "unit direction the axis of greatest spread is turned to"
+		)]
+		x: String,
+		#[clap(
+			long,
+			allow_hyphen_values = true,
+			value_name = "vector",
+			help = "unit direction, orthogonal to x, the axis of second greatest spread is turned to"
+		)]
+		y: String,
+	},
+	/// Prints the smallest sphere enclosing all vertices, or scales object into the unit sphere
+	BoundingSphere {
+		#[clap(
+			long,
+			help = "Centers object at the origin and scales it to fit the unit sphere instead of printing"
+		)]
+		normalize: bool,
+	},
+	/// Prints a histogram of vertex coordinates along an axis
+	Histogram {
+		#[clap(long, value_enum, default_value = "z")]
+		axis: Axis,
+		#[clap(long, default_value_t = 10)]
+		bins: usize,
 	},
 }
 
+impl Commands {
+	/// Whether the command reads all of its input before writing any output
+	fn reads_whole_input(&self) -> bool {
+		matches!(
+			self,
+			Commands::EdgeAlign { .. }
+				| Commands::SurfaceFalloff { .. }
+				| Commands::SnapToPlanes { .. }
+				| Commands::CurvatureDisplace { .. }
+				| Commands::SphereNormalize { .. }
+				| Commands::BoundingSphere { .. }
+				| Commands::FrameAlign { .. }
+				| Commands::Sag { .. }
+				| Commands::Ease { .. }
+				| Commands::SetOrigin { .. }
+				| Commands::Array { .. }
+				| Commands::Simplify { .. }
+				| Commands::Symmetry { .. }
+				| Commands::Footprint { outline: true, .. }
+				| Commands::Histogram { .. }
+		)
+	}
+}
+
 #[derive(Parser)]
 #[clap(author, version, about, long_about = None)]
 struct Args {
 	#[clap(subcommand)]
 	command: Commands,
+	#[clap(flatten)]
+	options: Options,
+}
+
+/// A single transform written as it would be on the command line
+#[derive(Parser)]
+#[clap(no_binary_name = true)]
+struct TransformSpec {
+	#[clap(subcommand)]
+	command: Commands,
+}
+
+/// Splits a command line on whitespace, keeping double quoted runs together
+fn split_arguments(s: &str) -> Vec<String> {
+	let mut arguments = Vec::new();
+	let mut current = String::new();
+	let mut quoted = false;
+	let mut started = false;
+	for c in s.chars() {
+		match c {
+			'"' => {
+				quoted = !quoted;
+				started = true;
+			}
+			c if c.is_whitespace() && !quoted => {
+				if started {
+					arguments.push(std::mem::take(&mut current));
+					started = false;
+				}
+			}
+			c => {
+				current.push(c);
+				started = true;
+			}
+		}
+	}
+	if started {
+		arguments.push(current);
+	}
+	arguments
+}
+
+fn parse_transform(s: &str, lenient: bool) -> Result<Box<dyn Transformer>, String> {
+	let spec = TransformSpec::try_parse_from(split_arguments(s)).map_err(|e| e.to_string())?;
+	build_transformer(spec.command, lenient)
+}
+
+#[derive(clap::Args)]
+struct Options {
+	#[clap(long, global = true, value_parser = parse_xyz_columns, value_name="layout", help="Reads lines of bare numbers as XYZ points with comma separated columns, e.g. x,y,z,nx,ny,nz or x,y,z,r,g,b")]
+	xyz_columns: Option<XyzLayout>,
+	#[clap(
+		long,
+		global = true,
+		conflicts_with = "xyz_columns",
+		help = "Reads every whitespace separated triple of numbers as a vertex"
+	)]
+	raw: bool,
+	#[clap(
+		long,
+		global = true,
+		value_name = "D",
+		value_parser = parse_distance,
+		help = "Limits how far any vertex may move from its original position"
+	)]
+	max_displacement: Option<f32>,
+	#[clap(
+		long,
+		global = true,
+		help = "Prepends comments recording the version, command and time"
+	)]
+	stamp: bool,
+	#[clap(
+		long,
+		global = true,
+		help = "Snaps the rotation of an affine transform to the nearest multiple of 90 degrees per axis"
+	)]
+	orthogonalize: bool,
+	#[clap(
+		long,
+		global = true,
+		help = "Reports the condition number of an affine transform on stderr"
+	)]
+	condition: bool,
+	#[clap(
+		long,
+		global = true,
+		help = "Prints a numpy snippet reproducing an affine transform instead of transforming"
+	)]
+	emit_python: bool,
+	#[clap(
+		long,
+		global = true,
+		help = "Splits polygonal faces into triangle fans"
+	)]
+	triangulate: bool,
+	#[clap(
+		long,
+		global = true,
+		value_name = "N",
+		help = "Relaxes vertices toward the area weighted centroid of their faces N times before transforming"
+	)]
+	relax_area: Option<usize>,
+	#[clap(
+		long,
+		global = true,
+		value_name = "N",
+		help = "Flushes output after every N input lines. Not available for commands that read the whole input"
+	)]
+	chunk_lines: Option<NonZeroUsize>,
+	#[clap(
+		long,
+		global = true,
+		value_name = "PATH",
+		help = "Manifest of \"r,g,b transform\" lines applying a transform to vertices of that color instead of the command's"
+	)]
+	by_color: Option<PathBuf>,
+	#[clap(
+		long,
+		global = true,
+		help = "Encodes the output as base64, e.g. for a data URI"
+	)]
+	base64: bool,
+	#[clap(
+		long,
+		global = true,
+		help = "Transforms only the largest set of vertices connected by faces"
+	)]
+	largest_component_only: bool,
+	#[clap(
+		long,
+		global = true,
+		help = "Accepts vectors and vertices separated by commas, spaces or both"
+	)]
+	lenient_parse: bool,
+	#[clap(
+		long,
+		global = true,
+		default_value_t = 1e-3,
+		help = "Distance within which a vertex color matches a manifest color"
+	)]
+	color_tolerance: f32,
+	#[clap(
+		long,
+		global = true,
+		value_name = "bits",
+		value_parser = clap::value_parser!(u8).range(2..=16),
+		help = "Round trips vn normals through an octahedral encoding with this many bits per component"
+	)]
+	octahedral_normals: Option<u8>,
+	#[clap(
+		long,
+		global = true,
+		help = "Maps vertices through the row major matrix of the latest \"# frame\" comment before transforming"
+	)]
+	apply_frames: bool,
+	#[clap(
+		long,
+		global = true,
+		help = "Reports on stderr how long transforming a sample of the vertices took"
+	)]
+	profile_transform: bool,
+}
+
+impl Options {
+	/// Fails naming the first given option that adjusts the transform, for commands
+	/// that write their result directly rather than transforming each vertex
+	fn reject_transform_options(&self, command: &str) -> Result<(), String> {
+		let given = [
+			("--max-displacement", self.max_displacement.is_some()),
+			("--orthogonalize", self.orthogonalize),
+			("--condition", self.condition),
+			("--emit-python", self.emit_python),
+			("--by-color", self.by_color.is_some()),
+			("--largest-component-only", self.largest_component_only),
+			("--relax-area", self.relax_area.is_some()),
+			("--profile-transform", self.profile_transform),
+		];
+		match given.iter().find(|(_, set)| *set) {
+			Some((flag, _)) => Err(format!("{} cannot be used with {}.", flag, command)),
+			None => Ok(()),
+		}
+	}
 }
 
 trait Transformer {
 	fn transform(&self, pt: Vector3<f32>) -> Vector3<f32>;
+
+	/// Transforms the vertex at a zero based position in the input, for transforms
+	/// computed over the whole mesh rather than from coordinates alone
+	fn transform_vertex(&self, _index: usize, pt: Vector3<f32>) -> Vector3<f32> {
+		self.transform(pt)
+	}
+
+	/// Transforms the vertex at a zero based position given the rgb color on its
+	/// line, for transforms chosen by vertex color
+	fn transform_colored(
+		&self,
+		index: usize,
+		pt: Vector3<f32>,
+		_color: Option<Vector3<f32>>,
+	) -> Vector3<f32> {
+		self.transform_vertex(index, pt)
+	}
+
+	/// Homogeneous matrix of the transform if it is affine
+	fn affine(&self) -> Option<Matrix4<f32>> {
+		None
+	}
+
+	/// Estimates the jacobian at pt with central differences
+	fn jacobian(&self, pt: Vector3<f32>) -> Matrix3<f32> {
+		let h = 1e-3 * (1.0 + pt.norm());
+		let mut jacobian = Matrix3::zeros();
+		for i in 0..3 {
+			let step = Vector3::ith(i, h);
+			let column = (self.transform(pt + step) - self.transform(pt - step)) / (2.0 * h);
+			jacobian.set_column(i, &column);
+		}
+		jacobian
+	}
+
+	/// Transforms a surface normal at pt by the inverse transpose of the jacobian
+	fn transform_normal(&self, pt: Vector3<f32>, normal: Vector3<f32>) -> Vector3<f32> {
+		match self.jacobian(pt).try_inverse() {
+			Some(inverse) => (inverse.transpose() * normal).normalize(),
+			None => normal,
+		}
+	}
+}
+
+fn transform_xyz_line(
+	transformer: &dyn Transformer,
+	columns: &[XyzColumn],
+	words: &[&str],
+) -> Option<String> {
+	if words.len() != columns.len() {
+		return None;
+	}
+	let mut values = Vec::with_capacity(words.len());
+	for word in words {
+		values.push(word.parse::<f32>().ok()?);
+	}
+
+	let index = |column| columns.iter().position(|&c| c == column);
+	let point = |a, b, c| {
+		Some(Vector3::new(
+			values[index(a)?],
+			values[index(b)?],
+			values[index(c)?],
+		))
+	};
+
+	let position = point(XyzColumn::X, XyzColumn::Y, XyzColumn::Z)?;
+	let normal = point(XyzColumn::Nx, XyzColumn::Ny, XyzColumn::Nz)
+		.map(|normal| transformer.transform_normal(position, normal));
+	let position = transformer.transform(position);
+
+	let fields: Vec<String> = columns
+		.iter()
+		.zip(words)
+		.map(|(column, word)| match (column, normal) {
+			(XyzColumn::X, _) => position.x.to_string(),
+			(XyzColumn::Y, _) => position.y.to_string(),
+			(XyzColumn::Z, _) => position.z.to_string(),
+			(XyzColumn::Nx, Some(n)) => n.x.to_string(),
+			(XyzColumn::Ny, Some(n)) => n.y.to_string(),
+			(XyzColumn::Nz, Some(n)) => n.z.to_string(),
+			_ => word.to_string(),
+		})
+		.collect();
+	Some(fields.join(" "))
 }
 
 struct WarpTransformer {
@@ -76,13 +1018,10 @@ impl WarpTransformer {
 	fn new(lines: Vec<Line>) -> Self {
 		let transforms: Vec<Matrix3<f32>> = Self::create_transformation_matrices(lines.clone())
 			.iter()
-			.map(|isometry| isometry.rotation.to_rotation_matrix().matrix().clone())
+			.map(|isometry| *isometry.rotation.to_rotation_matrix().matrix())
 			.collect();
 
-		WarpTransformer {
-			lines: lines,
-			transforms: transforms,
-		}
+		WarpTransformer { lines, transforms }
 	}
 
 	fn perpendicular_distance(point: Vector3<f32>, line: Line) -> f32 {
@@ -124,6 +1063,38 @@ impl WarpTransformer {
 		transformation_matrices
 	}
 
+	fn merge_collinear(lines: Vec<Line>, angle_eps: f32) -> Vec<Line> {
+		let mut groups: Vec<Vec<Line>> = Vec::new();
+		for line in lines {
+			let dir = (line.heading - line.origin).normalize();
+			let group = groups.iter_mut().find(|group| {
+				let first = group[0];
+				(first.heading - first.origin).normalize().angle(&dir) <= angle_eps
+			});
+			match group {
+				Some(group) => group.push(line),
+				None => groups.push(vec![line]),
+			}
+		}
+
+		groups
+			.iter()
+			.map(|group| {
+				let n = group.len() as f32;
+				let origin = group.iter().map(|line| line.origin).sum::<Vector3<f32>>() / n;
+				let dir = group
+					.iter()
+					.map(|line| (line.heading - line.origin).normalize())
+					.sum::<Vector3<f32>>()
+					/ n;
+				Line {
+					origin,
+					heading: origin + dir,
+				}
+			})
+			.collect()
+	}
+
 	fn interpolate_transforms(transforms: &[Matrix3<f32>], weights: &[f32]) -> Matrix3<f32> {
 		assert_eq!(
 			transforms.len(),
@@ -165,6 +1136,10 @@ impl Transformer for TranslateTransformer {
 	fn transform(&self, pt: Vector3<f32>) -> Vector3<f32> {
 		pt + self.xyz
 	}
+
+	fn affine(&self) -> Option<Matrix4<f32>> {
+		Some(Matrix4::new_translation(&self.xyz))
+	}
 }
 
 struct RotateTransformer {
@@ -184,6 +1159,11 @@ impl Transformer for RotateTransformer {
 
 		term1 + term2 + term3
 	}
+
+	fn affine(&self) -> Option<Matrix4<f32>> {
+		let axis = Unit::new_normalize(self.axis);
+		Some(Rotation3::from_axis_angle(&axis, self.angle).to_homogeneous())
+	}
 }
 
 struct ScaleTransformer {
@@ -194,52 +1174,4215 @@ impl Transformer for ScaleTransformer {
 	fn transform(&self, pt: Vector3<f32>) -> Vector3<f32> {
 		Vector3::new(pt.x * self.xyz.x, pt.y * self.xyz.y, pt.z * self.xyz.z)
 	}
+
+	fn affine(&self) -> Option<Matrix4<f32>> {
+		Some(Matrix4::new_nonuniform_scaling(&self.xyz))
+	}
 }
 
-fn main() {
-	let args = Args::parse();
+struct WaveTransformer {
+	direction: Vector3<f32>,
+	displacement: Vector3<f32>,
+	amplitude: f32,
+	wavelength: f32,
+	phase: f32,
+}
 
-	let transformer: Box<dyn Transformer> = match args.command {
-		Commands::Rotate { axis, angle } => Box::new(RotateTransformer {
-			axis: axis,
-			angle: angle,
-		}),
-		Commands::Translate { translation } => Box::new(TranslateTransformer { xyz: translation }),
-		Commands::Scale { scale } => Box::new(ScaleTransformer { xyz: scale }),
-		Commands::Warp { lines } => Box::new(WarpTransformer::new(match lines.len() {
-			0 => vec![
-				Line {
-					origin: Vector3::new(0f32, 0f32, 0f32),
-					heading: Vector3::new(1f32, 0f32, 0f32),
-				},
-				Line {
-					origin: Vector3::new(0f32, 0f32, 0f32),
-					heading: Vector3::new(0f32, 0f32, 1f32),
-				},
-			],
-			1 => {
-				eprintln!("A minimum of two lines is required.");
-				return;
+impl Transformer for WaveTransformer {
+	fn transform(&self, pt: Vector3<f32>) -> Vector3<f32> {
+		let u = self.direction.normalize();
+		let d = self.displacement.normalize();
+		let offset = self.amplitude
+			* (2.0 * std::f32::consts::PI * pt.dot(&u) / self.wavelength + self.phase).sin();
+
+		pt + d.scale(offset)
+	}
+}
+
+struct SweepTransformer {
+	axis: Vector3<f32>,
+	keys: Vec<Keyframe>,
+}
+
+impl SweepTransformer {
+	fn new(axis: Vector3<f32>, mut keys: Vec<Keyframe>) -> Self {
+		keys.sort_by(|a, b| a.t.total_cmp(&b.t));
+		SweepTransformer {
+			axis: axis.normalize(),
+			keys,
+		}
+	}
+
+	/// Smoothstep interpolation of angle and offset between the keyframes around t
+	fn interpolate(&self, t: f32) -> (f32, Vector3<f32>) {
+		let first = self.keys[0];
+		let last = self.keys[self.keys.len() - 1];
+		if t <= first.t {
+			return (first.angle, first.offset);
+		}
+		if t >= last.t {
+			return (last.angle, last.offset);
+		}
+
+		// only a NaN position is neither before, after nor between the keyframes
+		let Some(i) = self.keys.iter().position(|key| key.t > t) else {
+			return (f32::NAN, Vector3::repeat(f32::NAN));
+		};
+		let (a, b) = (self.keys[i - 1], self.keys[i]);
+		let s = (t - a.t) / (b.t - a.t);
+		let s = s * s * (3.0 - 2.0 * s);
+		(
+			a.angle + (b.angle - a.angle) * s,
+			a.offset.lerp(&b.offset, s),
+		)
+	}
+}
+
+impl Transformer for SweepTransformer {
+	fn transform(&self, pt: Vector3<f32>) -> Vector3<f32> {
+		let (angle, offset) = self.interpolate(pt.dot(&self.axis));
+		let rotation = RotateTransformer {
+			axis: self.axis,
+			angle,
+		};
+		rotation.transform(pt) + offset
+	}
+}
+
+enum Light {
+	Directional(Vector3<f32>),
+	Point(Vector3<f32>),
+}
+
+struct FootprintTransformer {
+	axis: usize,
+	offset: f32,
+	light: Light,
+}
+
+impl Transformer for FootprintTransformer {
+	fn transform(&self, pt: Vector3<f32>) -> Vector3<f32> {
+		let mut projected = match self.light {
+			Light::Directional(d) => pt - d * ((pt[self.axis] - self.offset) / d[self.axis]),
+			Light::Point(l) => {
+				l + (pt - l) * ((self.offset - l[self.axis]) / (pt[self.axis] - l[self.axis]))
 			}
-			_ => lines,
-		})),
+		};
+		projected[self.axis] = self.offset;
+		projected
+	}
+}
+
+/// Convex hull of points projected onto a plane, counter clockwise around the axis
+fn convex_outline(points: &[Vector3<f32>], axis: usize) -> Vec<Vector3<f32>> {
+	let (u, v) = ((axis + 1) % 3, (axis + 2) % 3);
+	let mut points = points.to_vec();
+	points.sort_by(|a, b| a[u].total_cmp(&b[u]).then(a[v].total_cmp(&b[v])));
+	points.dedup_by(|a, b| a[u] == b[u] && a[v] == b[v]);
+	if points.len() < 3 {
+		return points;
+	}
+
+	let cross = |o: Vector3<f32>, a: Vector3<f32>, b: Vector3<f32>| {
+		(a[u] - o[u]) * (b[v] - o[v]) - (a[v] - o[v]) * (b[u] - o[u])
 	};
+	let mut hull: Vec<Vector3<f32>> = Vec::new();
+	for pass in [points.clone(), points.into_iter().rev().collect()] {
+		let start = hull.len();
+		for p in pass {
+			while hull.len() >= start + 2
+				&& cross(hull[hull.len() - 2], hull[hull.len() - 1], p) <= 0.0
+			{
+				hull.pop();
+			}
+			hull.push(p);
+		}
+		hull.pop();
+	}
+	hull
+}
+
+struct SnapTransformer {
+	planes: [Vec<f32>; 3],
+	eps: f32,
+}
 
-	let stdin = io::stdin();
-	for text_line in stdin.lock().lines() {
-		let text_line = text_line.unwrap();
-		let words: Vec<&str> = text_line.split_whitespace().collect();
+impl SnapTransformer {
+	/// Groups the coordinates along each axis into runs no wider than eps and keeps
+	/// runs of at least min_vertices as planes, placed at the run's most common value
+	fn new(vertices: &[Vector3<f32>], eps: f32, min_vertices: usize) -> Self {
+		let planes = [0, 1, 2].map(|axis| {
+			let mut values: Vec<f32> = vertices.iter().map(|v| v[axis]).collect();
+			values.sort_by(f32::total_cmp);
 
-		if words[0] != "v" && words[0] != "vertex" || words.len() != 4 {
-			println!("{}", text_line);
-			continue;
+			let mut planes = Vec::new();
+			let mut start = 0;
+			while start < values.len() {
+				let end = values[start..]
+					.iter()
+					.position(|&v| v - values[start] > eps)
+					.map_or(values.len(), |n| start + n);
+				let run = &values[start..end];
+				if run.len() >= min_vertices {
+					planes.push(Self::most_common(run));
+				}
+				start = end;
+			}
+			planes
+		});
+		SnapTransformer { planes, eps }
+	}
+
+	/// Most repeated value of a sorted run, ties (including a run where no value
+	/// repeats) going to the value nearest the run's mean
+	fn most_common(run: &[f32]) -> f32 {
+		let mean = run.iter().sum::<f32>() / run.len() as f32;
+		let mut best = (run[0], 0, f32::INFINITY);
+		let mut i = 0;
+		while i < run.len() {
+			let count = run[i..].iter().take_while(|&&v| v == run[i]).count();
+			let distance = (run[i] - mean).abs();
+			if count > best.1 || (count == best.1 && distance < best.2) {
+				best = (run[i], count, distance);
+			}
+			i += count;
+		}
+		best.0
+	}
+}
+
+impl Transformer for SnapTransformer {
+	fn transform(&self, pt: Vector3<f32>) -> Vector3<f32> {
+		let mut snapped = pt;
+		for (axis, planes) in self.planes.iter().enumerate() {
+			let nearest = planes
+				.iter()
+				.map(|&plane| ((plane - pt[axis]).abs(), plane))
+				.filter(|&(distance, _)| distance <= self.eps)
+				.min_by(|a, b| a.0.total_cmp(&b.0));
+			if let Some((_, plane)) = nearest {
+				snapped[axis] = plane;
+			}
+		}
+		snapped
+	}
+}
+
+/// Thin plate spline with the 3D kernel |r|, mapping each control point exactly
+struct TpsTransformer {
+	points: Vec<Vector3<f64>>,
+	weights: DMatrix<f64>,
+	affine: DMatrix<f64>,
+}
+
+impl TpsTransformer {
+	fn new(from: &[Vector3<f32>], to: &[Vector3<f32>]) -> Result<Self, String> {
+		let n = from.len();
+		let points: Vec<Vector3<f64>> = from.iter().map(|p| p.cast()).collect();
+
+		let mut system = DMatrix::<f64>::zeros(n + 4, n + 4);
+		let mut targets = DMatrix::<f64>::zeros(n + 4, 3);
+		for i in 0..n {
+			for j in 0..n {
+				system[(i, j)] = (points[i] - points[j]).norm();
+			}
+			for k in 0..4 {
+				let value = if k == 0 { 1.0 } else { points[i][k - 1] };
+				system[(i, n + k)] = value;
+				system[(n + k, i)] = value;
+			}
+			for k in 0..3 {
+				targets[(i, k)] = to[i][k] as f64;
+			}
+		}
+
+		let solution = system
+			.lu()
+			.solve(&targets)
+			.ok_or("Control points must not all lie on one plane")?;
+		Ok(TpsTransformer {
+			points,
+			weights: solution.rows(0, n).into_owned(),
+			affine: solution.rows(n, 4).into_owned(),
+		})
+	}
+}
+
+impl Transformer for TpsTransformer {
+	fn transform(&self, pt: Vector3<f32>) -> Vector3<f32> {
+		let pt: Vector3<f64> = pt.cast();
+		let mut result = Vector3::<f64>::zeros();
+		for k in 0..3 {
+			result[k] = self.affine[(0, k)]
+				+ self.affine[(1, k)] * pt.x
+				+ self.affine[(2, k)] * pt.y
+				+ self.affine[(3, k)] * pt.z
+				+ self
+					.points
+					.iter()
+					.enumerate()
+					.map(|(i, p)| self.weights[(i, k)] * (pt - p).norm())
+					.sum::<f64>();
+		}
+		result.cast()
+	}
+}
+
+/// Parabolic sag, zero at the pinned ends and `strength` midway between them
+struct SagTransformer {
+	axis: usize,
+	min: f32,
+	max: f32,
+	gravity: Vector3<f32>,
+	strength: f32,
+}
+
+impl Transformer for SagTransformer {
+	fn transform(&self, pt: Vector3<f32>) -> Vector3<f32> {
+		let half_span = (self.max - self.min) / 2.0;
+		let t = ((pt[self.axis] - self.min) * (self.max - pt[self.axis])).max(0.0);
+		pt + self.gravity * (self.strength * t / (half_span * half_span))
+	}
+}
+
+/// Scales each vertex's offset from the view axis by 1 / (1 + strength * distance)
+struct FakeDepthTransformer {
+	camera: Vector3<f32>,
+	look: Vector3<f32>,
+	strength: f32,
+}
+
+impl Transformer for FakeDepthTransformer {
+	fn transform(&self, pt: Vector3<f32>) -> Vector3<f32> {
+		let on_axis = self.camera + self.look * (pt - self.camera).dot(&self.look);
+		let factor = 1.0 / (1.0 + self.strength * (pt - self.camera).norm());
+		on_axis + (pt - on_axis) * factor
+	}
+}
+
+struct RigidTransformer {
+	isometry: Isometry3<f32>,
+}
+
+impl RigidTransformer {
+	/// Finds the rigid motion best mapping `from` onto `to` with the Kabsch algorithm
+	fn kabsch(from: &[Vector3<f32>], to: &[Vector3<f32>]) -> Self {
+		let n = from.len() as f32;
+		let from_centroid = from.iter().sum::<Vector3<f32>>() / n;
+		let to_centroid = to.iter().sum::<Vector3<f32>>() / n;
+
+		let mut covariance = Matrix3::zeros();
+		for (p, q) in from.iter().zip(to.iter()) {
+			covariance += (p - from_centroid) * (q - to_centroid).transpose();
+		}
+
+		let svd = covariance.svd(true, true);
+		let u = svd.u.unwrap();
+		let v = svd.v_t.unwrap().transpose();
+		let d = (v * u.transpose()).determinant().signum();
+		let rotation = v * Matrix3::from_diagonal(&Vector3::new(1.0, 1.0, d)) * u.transpose();
+		let rotation = Rotation3::from_matrix_unchecked(rotation);
+		let translation = Translation3::from(to_centroid - rotation * from_centroid);
+
+		RigidTransformer {
+			isometry: Isometry3::from_parts(translation, rotation.into()),
+		}
+	}
+}
+
+impl Transformer for RigidTransformer {
+	fn transform(&self, pt: Vector3<f32>) -> Vector3<f32> {
+		(self.isometry * Point3::from(pt)).coords
+	}
+
+	fn affine(&self) -> Option<Matrix4<f32>> {
+		Some(self.isometry.to_homogeneous())
+	}
+}
+
+struct AffineTransformer {
+	matrix: Matrix4<f32>,
+}
+
+impl AffineTransformer {
+	/// Replaces the rotation of the linear part with the nearest signed permutation,
+	/// keeping the stretch of its polar decomposition and the translation. A stretch
+	/// that is uniform within UNIFORM_STRETCH_TOLERANCE is kept as an exact scale
+	fn orthogonalize(matrix: Matrix4<f32>) -> Matrix4<f32> {
+		const UNIFORM_STRETCH_TOLERANCE: f64 = 1e-5;
+
+		let linear = matrix.fixed_view::<3, 3>(0, 0).into_owned().cast::<f64>();
+		let svd = linear.svd(true, true);
+		let (u, v_t) = (svd.u.unwrap(), svd.v_t.unwrap());
+		let rotation = u * v_t;
+		let singular = svd.singular_values;
+		let stretch =
+			if singular.max() - singular.min() <= UNIFORM_STRETCH_TOLERANCE * singular.max() {
+				Matrix3::from_diagonal_element(singular.product().cbrt())
+			} else {
+				v_t.transpose() * Matrix3::from_diagonal(&singular) * v_t
+			};
+
+		let mut snapped = Matrix3::zeros();
+		let mut rows = vec![0, 1, 2];
+		let mut cols = vec![0, 1, 2];
+		while !rows.is_empty() {
+			let (i, j) = rows
+				.iter()
+				.flat_map(|&i| cols.iter().map(move |&j| (i, j)))
+				.max_by(|&a, &b| rotation[a].abs().total_cmp(&rotation[b].abs()))
+				.unwrap();
+			snapped[(i, j)] = rotation[(i, j)].signum();
+			rows.retain(|&r| r != i);
+			cols.retain(|&c| c != j);
 		}
 
-		let x = words[1].parse::<f32>().unwrap();
-		let y = words[2].parse::<f32>().unwrap();
-		let z = words[3].parse::<f32>().unwrap();
-		let output = transformer.transform(Vector3::new(x, y, z));
+		let mut result = matrix;
+		result
+			.fixed_view_mut::<3, 3>(0, 0)
+			.copy_from(&(snapped * stretch).cast::<f32>());
+		result
+	}
+}
 
-		println!("{} {} {} {}", words[0], output.x, output.y, output.z);
+impl Transformer for AffineTransformer {
+	fn transform(&self, pt: Vector3<f32>) -> Vector3<f32> {
+		self.matrix.transform_point(&Point3::from(pt)).coords
+	}
+
+	fn affine(&self) -> Option<Matrix4<f32>> {
+		Some(self.matrix)
+	}
+}
+
+/// Applies transformers one after another
+struct ComposedTransformer {
+	steps: Vec<Box<dyn Transformer>>,
+}
+
+impl Transformer for ComposedTransformer {
+	fn transform(&self, pt: Vector3<f32>) -> Vector3<f32> {
+		self.steps.iter().fold(pt, |pt, step| step.transform(pt))
+	}
+
+	fn affine(&self) -> Option<Matrix4<f32>> {
+		self.steps
+			.iter()
+			.try_fold(Matrix4::identity(), |matrix, step| {
+				Some(step.affine()? * matrix)
+			})
+	}
+}
+
+/// Places vertices given in a local coordinate frame before transforming them
+struct FramedTransformer<'a> {
+	frame: Matrix4<f32>,
+	inner: &'a dyn Transformer,
+}
+
+impl Transformer for FramedTransformer<'_> {
+	fn transform(&self, pt: Vector3<f32>) -> Vector3<f32> {
+		self.inner
+			.transform(self.frame.transform_point(&pt.into()).coords)
+	}
+
+	fn transform_vertex(&self, index: usize, pt: Vector3<f32>) -> Vector3<f32> {
+		self.transform_colored(index, pt, None)
+	}
+
+	fn transform_colored(
+		&self,
+		index: usize,
+		pt: Vector3<f32>,
+		color: Option<Vector3<f32>>,
+	) -> Vector3<f32> {
+		self.inner
+			.transform_colored(index, self.frame.transform_point(&pt.into()).coords, color)
+	}
+}
+
+/// Moves each vertex a fraction of the way to where another transformer takes it
+struct LerpTransformer<'a> {
+	inner: &'a dyn Transformer,
+	t: f32,
+}
+
+impl Transformer for LerpTransformer<'_> {
+	fn transform(&self, pt: Vector3<f32>) -> Vector3<f32> {
+		pt.lerp(&self.inner.transform(pt), self.t)
+	}
+
+	fn transform_vertex(&self, index: usize, pt: Vector3<f32>) -> Vector3<f32> {
+		pt.lerp(&self.inner.transform_vertex(index, pt), self.t)
+	}
+}
+
+/// Fraction t of an affine transform, splitting it into translation, rotation and
+/// stretch and interpolating them linearly, by slerp and in log scale respectively.
+/// None when the transform reflects or flattens, which has no such path
+fn interpolate_affine(matrix: &Matrix4<f32>, t: f32) -> Option<Matrix4<f32>> {
+	let linear = matrix.fixed_view::<3, 3>(0, 0).into_owned();
+	let translation = matrix.fixed_view::<3, 1>(0, 3).into_owned();
+	let svd = linear.svd(true, true);
+	let (u, v_t) = (svd.u?, svd.v_t?);
+	let rotation = u * v_t;
+	if rotation.determinant() < 0.0 || svd.singular_values.min() <= 0.0 {
+		return None;
+	}
+
+	let rotation = UnitQuaternion::from_matrix(&rotation);
+	let rotation = UnitQuaternion::identity().slerp(&rotation, t);
+	let stretch =
+		v_t.transpose() * Matrix3::from_diagonal(&svd.singular_values.map(|s| s.powf(t))) * v_t;
+
+	let mut result = (rotation.to_rotation_matrix().matrix() * stretch).to_homogeneous();
+	result
+		.fixed_view_mut::<3, 1>(0, 3)
+		.copy_from(&(translation * t));
+	Some(result)
+}
+
+struct ClampTransformer {
+	inner: Box<dyn Transformer>,
+	max_displacement: f32,
+}
+
+impl Transformer for ClampTransformer {
+	fn transform(&self, pt: Vector3<f32>) -> Vector3<f32> {
+		let displacement = self.inner.transform(pt) - pt;
+		pt + displacement.cap_magnitude(self.max_displacement)
+	}
+
+	fn transform_vertex(&self, index: usize, pt: Vector3<f32>) -> Vector3<f32> {
+		self.transform_colored(index, pt, None)
+	}
+
+	fn transform_colored(
+		&self,
+		index: usize,
+		pt: Vector3<f32>,
+		color: Option<Vector3<f32>>,
+	) -> Vector3<f32> {
+		let displacement = self.inner.transform_colored(index, pt, color) - pt;
+		pt + displacement.cap_magnitude(self.max_displacement)
+	}
+}
+
+/// Sphere through up to four points with all of them on its boundary. Degenerate
+/// triples and quadruples fall back to the smallest sphere through fewer of them
+fn boundary_sphere(points: &[Vector3<f64>]) -> (Vector3<f64>, f64) {
+	match points {
+		[] => (Vector3::zeros(), 0.0),
+		[p] => (*p, 0.0),
+		[p, q] => ((p + q) / 2.0, (p - q).norm() / 2.0),
+		[p, q, r] => {
+			let (a, b) = (q - p, r - p);
+			let normal = a.cross(&b);
+			if normal.norm_squared() <= f64::EPSILON * a.norm_squared() * b.norm_squared() {
+				return enclosing_fallback(points);
+			}
+			let offset = (b.cross(&normal) * a.norm_squared()
+				+ normal.cross(&a) * b.norm_squared())
+				/ (2.0 * normal.norm_squared());
+			(p + offset, offset.norm())
+		}
+		[p, rest @ ..] => {
+			let rows = Matrix3::from_rows(&[
+				(rest[0] - p).transpose(),
+				(rest[1] - p).transpose(),
+				(rest[2] - p).transpose(),
+			]);
+			let sides = Vector3::from_fn(|i, _| (rest[i] - p).norm_squared() / 2.0);
+			match rows.lu().solve(&sides) {
+				Some(offset) if offset.iter().all(|c| c.is_finite()) => (p + offset, offset.norm()),
+				_ => enclosing_fallback(points),
+			}
+		}
+	}
+}
+
+/// Smallest of the spheres through all but one of the points that still encloses them all
+fn enclosing_fallback(points: &[Vector3<f64>]) -> (Vector3<f64>, f64) {
+	(0..points.len())
+		.map(|skip| {
+			let subset: Vec<Vector3<f64>> = (0..points.len())
+				.filter(|&i| i != skip)
+				.map(|i| points[i])
+				.collect();
+			boundary_sphere(&subset)
+		})
+		.filter(|&(center, radius)| points.iter().all(|p| !outside_sphere(p, center, radius)))
+		.min_by(|a, b| a.1.total_cmp(&b.1))
+		.unwrap_or_else(|| boundary_sphere(&points[..2]))
+}
+
+fn outside_sphere(pt: &Vector3<f64>, center: Vector3<f64>, radius: f64) -> bool {
+	(pt - center).norm() > radius * (1.0 + 1e-9) + 1e-12
+}
+
+/// Smallest sphere enclosing the points with Welzl's algorithm, unrolled into loops
+/// over the points that must lie on the boundary. The points are shuffled first so
+/// the expected running time is linear whatever their order in the file
+fn minimal_enclosing_sphere(vertices: &[Vector3<f32>]) -> (Vector3<f32>, f32) {
+	let mut points: Vec<Vector3<f64>> = vertices.iter().map(|v| v.cast()).collect();
+	let mut state: u64 = 0x9e37_79b9_7f4a_7c15;
+	for i in (1..points.len()).rev() {
+		state ^= state << 13;
+		state ^= state >> 7;
+		state ^= state << 17;
+		points.swap(i, (state % (i as u64 + 1)) as usize);
+	}
+
+	let (mut center, mut radius) = boundary_sphere(&points[..1]);
+	for i in 1..points.len() {
+		if !outside_sphere(&points[i], center, radius) {
+			continue;
+		}
+		(center, radius) = boundary_sphere(&[points[i]]);
+		for j in 0..i {
+			if !outside_sphere(&points[j], center, radius) {
+				continue;
+			}
+			(center, radius) = boundary_sphere(&[points[i], points[j]]);
+			for k in 0..j {
+				if !outside_sphere(&points[k], center, radius) {
+					continue;
+				}
+				(center, radius) = boundary_sphere(&[points[i], points[j], points[k]]);
+				for l in 0..k {
+					if outside_sphere(&points[l], center, radius) {
+						(center, radius) =
+							boundary_sphere(&[points[i], points[j], points[k], points[l]]);
+					}
+				}
+			}
+		}
+	}
+	(center.cast(), radius as f32)
+}
+
+/// Largest deviation from unit length or orthogonality accepted in a reference frame
+const FRAME_TOLERANCE: f32 = 1e-3;
+
+/// Centroid and right handed principal axes of a point cloud, as matrix columns from
+/// greatest to least spread. Each axis points toward the side the points skew to
+fn principal_axes(vertices: &[Vector3<f32>]) -> (Vector3<f32>, Matrix3<f32>) {
+	let centroid = vertices.iter().sum::<Vector3<f32>>() / vertices.len() as f32;
+	let covariance = vertices
+		.iter()
+		.map(|v| (v - centroid) * (v - centroid).transpose())
+		.sum::<Matrix3<f32>>();
+
+	let eigen = covariance.symmetric_eigen();
+	let mut order = [0, 1, 2];
+	order.sort_by(|&a, &b| eigen.eigenvalues[b].total_cmp(&eigen.eigenvalues[a]));
+	let mut axes = order.map(|i| eigen.eigenvectors.column(i).into_owned());
+	for axis in &mut axes[..2] {
+		let skew: f32 = vertices
+			.iter()
+			.map(|v| (v - centroid).dot(axis).powi(3))
+			.sum();
+		if skew < 0.0 {
+			*axis = -*axis;
+		}
+	}
+	axes[2] = axes[0].cross(&axes[1]);
+	(centroid, Matrix3::from_columns(&axes))
+}
+
+/// Vertices between timed ones when profiling, keeping the clock reads off most vertices
+const PROFILE_STRIDE: usize = 8;
+
+/// Times every PROFILE_STRIDE-th vertex passed to another transformer
+struct ProfiledTransformer {
+	inner: Box<dyn Transformer>,
+	vertices: Cell<usize>,
+	samples: Rc<RefCell<Vec<Duration>>>,
+}
+
+impl ProfiledTransformer {
+	fn sample(&self, transform: impl FnOnce() -> Vector3<f32>) -> Vector3<f32> {
+		let count = self.vertices.get();
+		self.vertices.set(count + 1);
+		if !count.is_multiple_of(PROFILE_STRIDE) {
+			return transform();
+		}
+		let start = Instant::now();
+		let output = transform();
+		self.samples.borrow_mut().push(start.elapsed());
+		output
+	}
+}
+
+impl Transformer for ProfiledTransformer {
+	fn transform(&self, pt: Vector3<f32>) -> Vector3<f32> {
+		self.sample(|| self.inner.transform(pt))
+	}
+
+	fn transform_vertex(&self, index: usize, pt: Vector3<f32>) -> Vector3<f32> {
+		self.transform_colored(index, pt, None)
+	}
+
+	fn transform_colored(
+		&self,
+		index: usize,
+		pt: Vector3<f32>,
+		color: Option<Vector3<f32>>,
+	) -> Vector3<f32> {
+		self.sample(|| self.inner.transform_colored(index, pt, color))
+	}
+}
+
+fn profile_summary(samples: &mut [Duration]) -> String {
+	if samples.is_empty() {
+		return "profile: no vertices transformed".to_string();
+	}
+	samples.sort();
+	let percentile = |p: usize| samples[(samples.len() - 1) * p / 100].as_secs_f64() * 1e6;
+	let total: Duration = samples.iter().sum();
+	format!(
+		"profile: {} sampled vertices, mean {:.3}us, min {:.3}us, median {:.3}us, p90 {:.3}us, p99 {:.3}us, max {:.3}us",
+		samples.len(),
+		total.as_secs_f64() * 1e6 / samples.len() as f64,
+		percentile(0),
+		percentile(50),
+		percentile(90),
+		percentile(99),
+		percentile(100),
+	)
+}
+
+/// Feeds precomputed positions of each input vertex to another transformer
+struct RepositionTransformer {
+	positions: Vec<Vector3<f32>>,
+	inner: Box<dyn Transformer>,
+}
+
+impl Transformer for RepositionTransformer {
+	fn transform(&self, pt: Vector3<f32>) -> Vector3<f32> {
+		self.inner.transform(pt)
+	}
+
+	fn transform_vertex(&self, index: usize, pt: Vector3<f32>) -> Vector3<f32> {
+		self.transform_colored(index, pt, None)
+	}
+
+	fn transform_colored(
+		&self,
+		index: usize,
+		pt: Vector3<f32>,
+		color: Option<Vector3<f32>>,
+	) -> Vector3<f32> {
+		let pt = self.positions.get(index).copied().unwrap_or(pt);
+		self.inner.transform_colored(index, pt, color)
+	}
+}
+
+/// A color and the transform applied to vertices of that color
+type ColorEntry = (Vector3<f32>, Box<dyn Transformer>);
+
+/// Transforms each vertex by the manifest entry matching its color, or by a default
+struct ColorTransformer {
+	entries: Vec<ColorEntry>,
+	tolerance: f32,
+	inner: Box<dyn Transformer>,
+}
+
+impl ColorTransformer {
+	fn read_manifest(path: &Path, lenient: bool) -> Result<Vec<ColorEntry>, String> {
+		let text =
+			std::fs::read_to_string(path).map_err(|e| format!("Failed to read manifest: {}", e))?;
+		let mut entries = Vec::new();
+		for text_line in text.lines().map(str::trim) {
+			if text_line.is_empty() || text_line.starts_with('#') {
+				continue;
+			}
+			let (color, spec) = text_line
+				.split_once(char::is_whitespace)
+				.ok_or(format!("Missing transform in manifest line: {}", text_line))?;
+			let color = convert(color, lenient, parse_vector3)?;
+			entries.push((color, parse_transform(spec, lenient)?));
+		}
+		Ok(entries)
+	}
+
+	fn select(&self, color: Option<Vector3<f32>>) -> &dyn Transformer {
+		let entry = color.and_then(|color| {
+			self.entries
+				.iter()
+				.map(|(c, transformer)| ((c - color).norm(), transformer))
+				.filter(|(distance, _)| *distance <= self.tolerance)
+				.min_by(|a, b| a.0.total_cmp(&b.0))
+		});
+		match entry {
+			Some((_, transformer)) => transformer.as_ref(),
+			None => self.inner.as_ref(),
+		}
+	}
+}
+
+impl Transformer for ColorTransformer {
+	fn transform(&self, pt: Vector3<f32>) -> Vector3<f32> {
+		self.inner.transform(pt)
+	}
+
+	fn transform_vertex(&self, index: usize, pt: Vector3<f32>) -> Vector3<f32> {
+		self.inner.transform_vertex(index, pt)
+	}
+
+	fn transform_colored(
+		&self,
+		index: usize,
+		pt: Vector3<f32>,
+		color: Option<Vector3<f32>>,
+	) -> Vector3<f32> {
+		self.select(color).transform_vertex(index, pt)
+	}
+}
+
+/// Transforms only the input vertices selected by a mask
+struct MaskTransformer {
+	mask: Vec<bool>,
+	inner: Box<dyn Transformer>,
+}
+
+impl Transformer for MaskTransformer {
+	fn transform(&self, pt: Vector3<f32>) -> Vector3<f32> {
+		self.inner.transform(pt)
+	}
+
+	fn transform_vertex(&self, index: usize, pt: Vector3<f32>) -> Vector3<f32> {
+		self.transform_colored(index, pt, None)
+	}
+
+	fn transform_colored(
+		&self,
+		index: usize,
+		pt: Vector3<f32>,
+		color: Option<Vector3<f32>>,
+	) -> Vector3<f32> {
+		match self.mask.get(index) {
+			Some(true) => self.inner.transform_colored(index, pt, color),
+			_ => pt,
+		}
+	}
+}
+
+/// Moves each input vertex to a precomputed position
+struct IndexedTransformer {
+	positions: Vec<Vector3<f32>>,
+}
+
+impl Transformer for IndexedTransformer {
+	fn transform(&self, pt: Vector3<f32>) -> Vector3<f32> {
+		pt
+	}
+
+	fn transform_vertex(&self, index: usize, pt: Vector3<f32>) -> Vector3<f32> {
+		self.positions.get(index).copied().unwrap_or(pt)
+	}
+}
+
+/// Position of an obj or stl vertex line. Obj vertices may carry trailing fields
+/// such as a weight or an rgb color
+fn parse_vertex(words: &[&str]) -> Option<Vector3<f32>> {
+	match words.first() {
+		Some(&"v") if words.len() >= 4 => {}
+		Some(&"vertex") if words.len() == 4 => {}
+		_ => return None,
+	}
+	let x = words[1].parse::<f32>().ok()?;
+	let y = words[2].parse::<f32>().ok()?;
+	let z = words[3].parse::<f32>().ok()?;
+	Some(Vector3::new(x, y, z))
+}
+
+/// Rgb color of an obj vertex line written as v x y z r g b
+fn parse_vertex_color(words: &[&str]) -> Option<Vector3<f32>> {
+	if words.len() != 7 || words[0] != "v" {
+		return None;
+	}
+	parse_vertex(words)?;
+	let r = words[4].parse::<f32>().ok()?;
+	let g = words[5].parse::<f32>().ok()?;
+	let b = words[6].parse::<f32>().ok()?;
+	Some(Vector3::new(r, g, b))
+}
+
+/// Vertex line with a new position, keeping any trailing fields
+fn format_vertex(words: &[&str], pt: Vector3<f32>) -> String {
+	let mut fields = vec![
+		words[0].to_string(),
+		pt.x.to_string(),
+		pt.y.to_string(),
+		pt.z.to_string(),
+	];
+	fields.extend(words[4..].iter().map(|word| word.to_string()));
+	fields.join(" ")
+}
+
+fn read_vertices<R: BufRead>(reader: R, lenient: bool) -> io::Result<Vec<Vector3<f32>>> {
+	let mut vertices = Vec::new();
+	for text_line in reader.lines() {
+		let text_line = text_line?;
+		let words: Vec<&str> = split_words(&text_line, lenient);
+		if let Some(vertex) = parse_vertex(&words) {
+			vertices.push(vertex);
+		}
+	}
+	Ok(vertices)
+}
+
+/// Parses an exported 4x4 matrix into a column vector, Z up matrix
+fn parse_dcc_matrix(text: &str, format: DccFormat) -> Result<Matrix4<f32>, &'static str> {
+	let values: Vec<f32> = text
+		.split(|c: char| c.is_whitespace() || ",()[]<>".contains(c))
+		.filter_map(|word| word.parse::<f32>().ok())
+		.collect();
+	if values.len() != 16 {
+		return Err("Expected exactly sixteen matrix values");
+	}
+	let matrix = Matrix4::from_row_slice(&values);
+	Ok(match format {
+		DccFormat::Blender => matrix,
+		DccFormat::Maya => {
+			let y_up_to_z_up =
+				Rotation3::from_axis_angle(&Vector3::x_axis(), std::f32::consts::FRAC_PI_2)
+					.to_homogeneous();
+			y_up_to_z_up * matrix.transpose() * y_up_to_z_up.transpose()
+		}
+	})
+}
+
+/// Offsets the positive indices of an obj face or line element by the number of
+/// vertices, texture coordinates and normals preceding the copy
+fn offset_element(words: &[&str], offsets: [usize; 3]) -> String {
+	let corners: Vec<String> = words[1..]
+		.iter()
+		.map(|corner| {
+			let parts: Vec<String> = corner
+				.split('/')
+				.zip(offsets)
+				.map(|(part, offset)| match part.parse::<i64>() {
+					Ok(index) if index > 0 => (index + offset as i64).to_string(),
+					_ => part.to_string(),
+				})
+				.collect();
+			parts.join("/")
+		})
+		.collect();
+	format!("{} {}", words[0], corners.join(" "))
+}
+
+/// Copies the input once per grid cell, each copy rotated by its step and moved to its cell
+fn array_lines(
+	text: &[String],
+	count: [usize; 3],
+	spacing: Vector3<f32>,
+	step: RotateTransformer,
+	lenient: bool,
+) -> Vec<String> {
+	let mut totals = [0; 3];
+	for text_line in text {
+		match text_line.split_whitespace().next() {
+			Some("v") => totals[0] += 1,
+			Some("vt") => totals[1] += 1,
+			Some("vn") => totals[2] += 1,
+			_ => {}
+		}
+	}
+
+	let mut output = Vec::new();
+	let mut copy = 0;
+	for z in 0..count[2] {
+		for y in 0..count[1] {
+			for x in 0..count[0] {
+				let cell = Vector3::new(x as f32, y as f32, z as f32).component_mul(&spacing);
+				let rotation = RotateTransformer {
+					axis: step.axis,
+					angle: step.angle * copy as f32,
+				};
+				let offsets = totals.map(|total| total * copy);
+				for text_line in text {
+					let words: Vec<&str> = split_words(text_line, lenient);
+					output.push(match (parse_vertex(&words), words.first()) {
+						(Some(vertex), _) => {
+							format_vertex(&words, rotation.transform(vertex) + cell)
+						}
+						(None, Some(&"f")) | (None, Some(&"l")) => offset_element(&words, offsets),
+						_ => text_line.clone(),
+					});
+				}
+				copy += 1;
+			}
+		}
+	}
+	output
+}
+
+/// Reads warp lines from a file, skipping blank lines and # comments
+fn read_warp_lines(path: &Path, lenient: bool) -> Result<Vec<Line>, String> {
+	let text = std::fs::read_to_string(path).map_err(|e| format!("Failed to read lines: {}", e))?;
+	text.lines()
+		.map(str::trim)
+		.enumerate()
+		.filter(|(_, text_line)| !text_line.is_empty() && !text_line.starts_with('#'))
+		.map(|(number, text_line)| {
+			parse_line(text_line, lenient).map_err(|e| format!("{} on line {}", e, number + 1))
+		})
+		.collect()
+}
+
+fn read_lines<R: BufRead>(reader: R) -> io::Result<Vec<String>> {
+	reader.lines().collect()
+}
+
+/// Reads points written either as obj vertices or as bare "x y z" lines
+fn read_points_file(path: &Path, lenient: bool) -> io::Result<Vec<Vector3<f32>>> {
+	let mut points = Vec::new();
+	for text_line in BufReader::new(File::open(path)?).lines() {
+		let text_line = text_line?;
+		let words: Vec<&str> = split_words(&text_line, lenient);
+		if let Some(vertex) = parse_vertex(&words) {
+			points.push(vertex);
+		} else if let [x, y, z] = words[..] {
+			if let (Ok(x), Ok(y), Ok(z)) = (x.parse(), y.parse(), z.parse()) {
+				points.push(Vector3::new(x, y, z));
+			}
+		}
+	}
+	Ok(points)
+}
+
+/// Reads the vertices of an obj or stl file, failing on a malformed vertex rather
+/// than skipping it so vertices keep their positions in the file
+fn read_vertices_file(path: &Path, lenient: bool) -> io::Result<Vec<Vector3<f32>>> {
+	let mut vertices = Vec::new();
+	for text_line in BufReader::new(File::open(path)?).lines() {
+		let text_line = text_line?;
+		let words: Vec<&str> = split_words(&text_line, lenient);
+		match (parse_vertex(&words), words.first()) {
+			(Some(vertex), _) => vertices.push(vertex),
+			(None, Some(&"v" | &"vertex")) => {
+				return Err(io::Error::new(io::ErrorKind::InvalidData, "Invalid vertex"));
+			}
+			_ => {}
+		}
+	}
+	Ok(vertices)
+}
+
+/// Corners of a triangle along with the index of the face or triangle it came from
+type SourcedTriangle = (usize, [usize; 3]);
+
+/// Vertices and faces of an obj or stl, faces holding zero based vertex indices
+struct Mesh {
+	vertices: Vec<Vector3<f32>>,
+	faces: Vec<Vec<usize>>,
+	/// Zero based input line each face was read from
+	face_lines: Vec<usize>,
+}
+
+impl Mesh {
+	fn parse(lines: &[String], lenient: bool) -> Self {
+		let mut vertices = Vec::new();
+		let mut faces = Vec::new();
+		let mut face_lines = Vec::new();
+		let mut facet: Vec<usize> = Vec::new();
+		for (line_number, text_line) in lines.iter().enumerate() {
+			let words: Vec<&str> = split_words(text_line, lenient);
+			if let Some(vertex) = parse_vertex(&words) {
+				if words[0] == "vertex" {
+					facet.push(vertices.len());
+				}
+				vertices.push(vertex);
+				continue;
+			}
+			match words.first() {
+				Some(&"f") => {
+					let face: Option<Vec<usize>> = words[1..]
+						.iter()
+						.map(|corner| Self::parse_index(corner, vertices.len()))
+						.collect();
+					if let Some(face) = face {
+						faces.push(face);
+						face_lines.push(line_number);
+					}
+				}
+				Some(&"endloop") => {
+					faces.push(std::mem::take(&mut facet));
+					face_lines.push(line_number);
+				}
+				_ => {}
+			}
+		}
+		Mesh {
+			vertices,
+			faces,
+			face_lines,
+		}
+	}
+
+	/// Resolves the vertex part of an obj face corner such as 3, -1 or 3/1/2
+	fn parse_index(corner: &str, vertex_count: usize) -> Option<usize> {
+		let index = corner.split('/').next()?.parse::<i64>().ok()?;
+		let index = match index {
+			i if i > 0 => i - 1,
+			i if i < 0 => vertex_count as i64 + i,
+			_ => return None,
+		};
+		(0..vertex_count as i64)
+			.contains(&index)
+			.then_some(index as usize)
+	}
+
+	/// Index of the first vertex sharing each vertex's exact position, so stl facets
+	/// and obj seams are connected
+	fn welded(&self) -> Vec<usize> {
+		let mut first: HashMap<[u32; 3], usize> = HashMap::new();
+		self.vertices
+			.iter()
+			.enumerate()
+			.map(|(i, v)| {
+				*first
+					.entry([v.x.to_bits(), v.y.to_bits(), v.z.to_bits()])
+					.or_insert(i)
+			})
+			.collect()
+	}
+
+	/// Fan triangulated faces over welded vertices, without degenerate triangles
+	fn triangles(&self) -> Vec<[usize; 3]> {
+		self.face_triangles()
+			.into_iter()
+			.map(|(_, triangle)| triangle)
+			.collect()
+	}
+
+	/// Triangles as in `triangles`, each with the index of the face it was cut from
+	fn face_triangles(&self) -> Vec<SourcedTriangle> {
+		let welded = self.welded();
+		self.faces
+			.iter()
+			.enumerate()
+			.filter(|(_, face)| face.len() >= 3)
+			.flat_map(|(f, face)| {
+				(1..face.len() - 1).map(move |i| (f, [face[0], face[i], face[i + 1]]))
+			})
+			.map(|(f, triangle)| (f, triangle.map(|v| welded[v])))
+			.filter(|(_, [a, b, c])| a != b && b != c && a != c)
+			.collect()
+	}
+
+	/// Component label of each vertex, vertices being connected through shared faces
+	fn components(&self) -> Vec<usize> {
+		let mut parent: Vec<usize> = self.welded();
+		fn root(parent: &mut [usize], mut v: usize) -> usize {
+			while parent[v] != v {
+				parent[v] = parent[parent[v]];
+				v = parent[v];
+			}
+			v
+		}
+		for face in &self.faces {
+			for pair in face.windows(2) {
+				let (a, b) = (root(&mut parent, pair[0]), root(&mut parent, pair[1]));
+				parent[a.max(b)] = a.min(b);
+			}
+		}
+		(0..self.vertices.len())
+			.map(|v| root(&mut parent, v))
+			.collect()
+	}
+
+	/// Whether each vertex lies on an edge used by only one triangle
+	fn boundary(&self, triangles: &[[usize; 3]]) -> Vec<bool> {
+		let mut edge_uses: HashMap<(usize, usize), usize> = HashMap::new();
+		for &[a, b, c] in triangles {
+			for (p, q) in [(a, b), (b, c), (c, a)] {
+				*edge_uses.entry((p.min(q), p.max(q))).or_insert(0) += 1;
+			}
+		}
+		let mut boundary = vec![false; self.vertices.len()];
+		for (&(a, b), _) in edge_uses.iter().filter(|(_, &uses)| uses == 1) {
+			boundary[a] = true;
+			boundary[b] = true;
+		}
+		boundary
+	}
+
+	/// Signed mean curvature and area weighted normal of each vertex from the cotangent
+	/// Laplacian, zero on the boundary
+	fn mean_curvature(&self) -> (Vec<f32>, Vec<Vector3<f32>>) {
+		let welded = self.welded();
+		let triangles = self.triangles();
+		let boundary = self.boundary(&triangles);
+		let n = self.vertices.len();
+
+		let mut laplacian = vec![Vector3::zeros(); n];
+		let mut normals = vec![Vector3::zeros(); n];
+		let mut areas = vec![0f32; n];
+		for &triangle in &triangles {
+			let [a, b, c] = triangle.map(|v| self.vertices[v]);
+			let normal = (b - a).cross(&(c - a));
+			for k in 0..3 {
+				let (i, j, o) = (triangle[k], triangle[(k + 1) % 3], triangle[(k + 2) % 3]);
+				let (pi, pj, po) = (self.vertices[i], self.vertices[j], self.vertices[o]);
+				let cross = (pi - po).cross(&(pj - po)).norm();
+				if cross > 0.0 {
+					let cot = (pi - po).dot(&(pj - po)) / cross;
+					laplacian[i] += (pj - pi) * cot;
+					laplacian[j] += (pi - pj) * cot;
+				}
+				normals[i] += normal;
+				areas[i] += normal.norm() / 6.0;
+			}
+		}
+
+		let curvature: Vec<f32> = (0..n)
+			.map(|v| match boundary[v] || areas[v] == 0.0 {
+				true => 0.0,
+				false => -(laplacian[v] / (2.0 * areas[v])).dot(&normals[v].normalize()) / 2.0,
+			})
+			.collect();
+		(
+			welded.iter().map(|&w| curvature[w]).collect(),
+			welded
+				.iter()
+				.map(|&w| normals[w].try_normalize(0.0).unwrap_or_default())
+				.collect(),
+		)
+	}
+
+	/// Repeatedly moves interior vertices halfway toward the area weighted centroid
+	/// of their incident triangles, leaving boundary vertices in place
+	fn relax_area(&self, iterations: usize) -> Vec<Vector3<f32>> {
+		let welded = self.welded();
+		let triangles = self.triangles();
+
+		let fixed = self.boundary(&triangles);
+
+		let mut positions = self.vertices.clone();
+		for _ in 0..iterations {
+			let mut sums = vec![Vector3::zeros(); positions.len()];
+			let mut areas = vec![0f32; positions.len()];
+			for &triangle in &triangles {
+				let [a, b, c] = triangle.map(|v| positions[v]);
+				let area = (b - a).cross(&(c - a)).norm() / 2.0;
+				let centroid = (a + b + c) / 3.0;
+				for v in triangle {
+					sums[v] += centroid * area;
+					areas[v] += area;
+				}
+			}
+			for v in 0..positions.len() {
+				if welded[v] == v && !fixed[v] && areas[v] > 0.0 {
+					positions[v] = positions[v].lerp(&(sums[v] / areas[v]), 0.5);
+				}
+			}
+		}
+		welded.iter().map(|&w| positions[w]).collect()
+	}
+
+	/// Neighbours of each vertex along face edges, with coincident vertices welded
+	fn adjacency(&self) -> Vec<Vec<usize>> {
+		let welded = self.welded();
+		let mut adjacency = vec![Vec::new(); self.vertices.len()];
+		for (a, b) in self.edges() {
+			let (a, b) = (welded[a], welded[b]);
+			if a != b {
+				adjacency[a].push(b);
+				adjacency[b].push(a);
+			}
+		}
+		for list in adjacency.iter_mut() {
+			list.sort_unstable();
+			list.dedup();
+		}
+		adjacency
+	}
+
+	/// Shortest path lengths along edges from a vertex, infinite where unreachable
+	fn edge_distances(&self, seed: usize) -> Vec<f32> {
+		let welded = self.welded();
+		let adjacency = self.adjacency();
+		let mut distances = vec![f32::INFINITY; self.vertices.len()];
+		let mut queue = BinaryHeap::new();
+		distances[welded[seed]] = 0.0;
+		// bits of non negative floats sort in the same order as the floats
+		queue.push(Reverse((0f32.to_bits(), welded[seed])));
+		while let Some(Reverse((bits, a))) = queue.pop() {
+			let distance = f32::from_bits(bits);
+			if distance > distances[a] {
+				continue;
+			}
+			for &b in &adjacency[a] {
+				let next = distance + (self.vertices[b] - self.vertices[a]).norm();
+				if next < distances[b] {
+					distances[b] = next;
+					queue.push(Reverse((next.to_bits(), b)));
+				}
+			}
+		}
+		welded.iter().map(|&w| distances[w]).collect()
+	}
+
+	/// Unique undirected edges of all faces
+	fn edges(&self) -> Vec<(usize, usize)> {
+		let mut edges: Vec<(usize, usize)> = self
+			.faces
+			.iter()
+			.flat_map(|face| {
+				(0..face.len()).map(move |i| {
+					let (a, b) = (face[i], face[(i + 1) % face.len()]);
+					(a.min(b), a.max(b))
+				})
+			})
+			.filter(|(a, b)| a != b)
+			.collect();
+		edges.sort_unstable();
+		edges.dedup();
+		edges
+	}
+}
+
+/// Plane through a triangle as (normal, -normal·point), None when degenerate
+fn triangle_plane([a, b, c]: [Vector3<f64>; 3]) -> Option<Vector4<f64>> {
+	let normal = (b - a).cross(&(c - a)).try_normalize(1e-12)?;
+	Some(normal.push(-normal.dot(&a)))
+}
+
+/// Position minimising the combined quadric of an edge, and its error
+fn collapse_target(
+	quadric: &Matrix4<f64>,
+	a: Vector3<f64>,
+	b: Vector3<f64>,
+) -> (Vector3<f64>, f64) {
+	let error = |v: Vector3<f64>| {
+		let h = v.push(1.0);
+		(h.transpose() * quadric * h)[0].max(0.0)
+	};
+	let mut solve = *quadric;
+	solve.set_row(3, &Vector4::new(0.0, 0.0, 0.0, 1.0).transpose());
+	let optimal = solve
+		.try_inverse()
+		.map(|inverse| (inverse * Vector4::w()).xyz())
+		.filter(|v| v.iter().all(|c| c.is_finite()));
+	let candidates = [optimal, Some(a), Some(b), Some((a + b) / 2.0)];
+	candidates
+		.into_iter()
+		.flatten()
+		.map(|v| (v, error(v)))
+		.min_by(|x, y| x.1.total_cmp(&y.1))
+		.unwrap()
+}
+
+/// Collapses edges in order of quadric error until at most `target` triangles remain,
+/// skipping collapses that would pinch the surface or flip a triangle. Each remaining
+/// triangle comes with the index of the input triangle it is what is left of
+fn simplify(
+	vertices: &[Vector3<f32>],
+	triangles: &[[usize; 3]],
+	target: usize,
+) -> (Vec<Vector3<f32>>, Vec<SourcedTriangle>) {
+	let mut positions: Vec<Vector3<f64>> = vertices.iter().map(|v| v.cast()).collect();
+	let mut faces = triangles.to_vec();
+	let mut alive = vec![true; faces.len()];
+	let mut remaining = faces.len();
+
+	let mut incident = vec![Vec::new(); positions.len()];
+	let mut quadrics = vec![Matrix4::<f64>::zeros(); positions.len()];
+	for (f, &face) in faces.iter().enumerate() {
+		let plane = triangle_plane(face.map(|v| positions[v]));
+		for v in face {
+			incident[v].push(f);
+			if let Some(plane) = plane {
+				quadrics[v] += plane * plane.transpose();
+			}
+		}
+	}
+
+	let neighbours = |faces: &[[usize; 3]], incident: &[Vec<usize>], v: usize| {
+		let mut list: Vec<usize> = incident[v]
+			.iter()
+			.flat_map(|&f| faces[f])
+			.filter(|&n| n != v)
+			.collect();
+		list.sort_unstable();
+		list.dedup();
+		list
+	};
+
+	// entries are invalidated by bumping the version of either end
+	let mut version = vec![0usize; positions.len()];
+	let mut heap = BinaryHeap::new();
+	let mut edges: Vec<(usize, usize)> = triangles
+		.iter()
+		.flat_map(|&[a, b, c]| [(a, b), (b, c), (c, a)])
+		.map(|(a, b)| (a.min(b), a.max(b)))
+		.collect();
+	edges.sort_unstable();
+	edges.dedup();
+	for (a, b) in edges {
+		let (_, cost) = collapse_target(&(quadrics[a] + quadrics[b]), positions[a], positions[b]);
+		heap.push(Reverse((cost.to_bits(), a, b, 0, 0)));
+	}
+
+	while remaining > target {
+		let Some(Reverse((_, a, b, version_a, version_b))) = heap.pop() else {
+			break;
+		};
+		if version[a] != version_a || version[b] != version_b {
+			continue;
+		}
+
+		let quadric = quadrics[a] + quadrics[b];
+		let (target_position, _) = collapse_target(&quadric, positions[a], positions[b]);
+
+		let neighbours_a = neighbours(&faces, &incident, a);
+		let neighbours_b = neighbours(&faces, &incident, b);
+		let shared = neighbours_a
+			.iter()
+			.filter(|n| neighbours_b.contains(n))
+			.count();
+		if shared > 2 {
+			continue;
+		}
+
+		let flips = incident[a].iter().chain(incident[b].iter()).any(|&f| {
+			let face = faces[f];
+			if face.contains(&a) && face.contains(&b) {
+				return false;
+			}
+			let before = triangle_plane(face.map(|v| positions[v]));
+			let after = triangle_plane(face.map(|v| match v == a || v == b {
+				true => target_position,
+				false => positions[v],
+			}));
+			match (before, after) {
+				(Some(before), Some(after)) => before.xyz().dot(&after.xyz()) <= 0.0,
+				_ => false,
+			}
+		});
+		if flips {
+			continue;
+		}
+
+		positions[a] = target_position;
+		quadrics[a] = quadric;
+		for f in std::mem::take(&mut incident[b]) {
+			if faces[f].contains(&a) {
+				alive[f] = false;
+				remaining -= 1;
+			} else {
+				for v in faces[f].iter_mut().filter(|v| **v == b) {
+					*v = a;
+				}
+				incident[a].push(f);
+			}
+		}
+		incident[a].retain(|&f| alive[f]);
+		for n in neighbours(&faces, &incident, a) {
+			incident[n].retain(|&f| alive[f]);
+		}
+		version[a] += 1;
+		version[b] += 1;
+
+		for n in neighbours(&faces, &incident, a) {
+			let (_, cost) =
+				collapse_target(&(quadrics[a] + quadrics[n]), positions[a], positions[n]);
+			heap.push(Reverse((cost.to_bits(), a, n, version[a], version[n])));
+		}
+	}
+
+	let mut remap = vec![usize::MAX; positions.len()];
+	let mut kept = Vec::new();
+	let mut output_faces = Vec::new();
+	for (f, face) in faces.iter().enumerate().filter(|&(f, _)| alive[f]) {
+		let face = face.map(|v| {
+			if remap[v] == usize::MAX {
+				remap[v] = kept.len();
+				kept.push(positions[v].cast::<f32>());
+			}
+			remap[v]
+		});
+		output_faces.push((f, face));
+	}
+	(kept, output_faces)
+}
+
+/// Shortest rotation taking one direction onto another, including opposite directions
+fn minimal_rotation(from: Vector3<f32>, to: Vector3<f32>) -> Rotation3<f32> {
+	Rotation3::rotation_between(&from, &to).unwrap_or_else(|| {
+		let perpendicular = match from.cross(&Vector3::x()).try_normalize(1e-6) {
+			Some(perpendicular) => perpendicular,
+			None => from.cross(&Vector3::y()).normalize(),
+		};
+		Rotation3::from_axis_angle(&Unit::new_unchecked(perpendicular), std::f32::consts::PI)
+	})
+}
+
+/// Hash grid of points answering whether any point lies within a fixed radius
+struct PointGrid {
+	radius: f32,
+	cells: HashMap<[i64; 3], Vec<Vector3<f32>>>,
+}
+
+impl PointGrid {
+	fn new(points: &[Vector3<f32>], radius: f32) -> Self {
+		let mut grid = PointGrid {
+			radius,
+			cells: HashMap::new(),
+		};
+		for &p in points {
+			grid.cells.entry(grid.cell(p)).or_default().push(p);
+		}
+		grid
+	}
+
+	fn cell(&self, p: Vector3<f32>) -> [i64; 3] {
+		[0, 1, 2].map(|i| (p[i] / self.radius).floor() as i64)
+	}
+
+	fn any_within(&self, p: Vector3<f32>) -> bool {
+		let [x, y, z] = self.cell(p);
+		(-1..=1).any(|dx| {
+			(-1..=1).any(|dy| {
+				(-1..=1).any(|dz| {
+					self.cells
+						.get(&[x + dx, y + dy, z + dz])
+						.is_some_and(|cell| cell.iter().any(|q| (q - p).norm() <= self.radius))
+				})
+			})
+		})
+	}
+}
+
+fn bounding_box(vertices: &[Vector3<f32>]) -> (Vector3<f32>, Vector3<f32>) {
+	vertices.iter().fold(
+		(
+			Vector3::repeat(f32::INFINITY),
+			Vector3::repeat(f32::NEG_INFINITY),
+		),
+		|(min, max), v| (min.inf(v), max.sup(v)),
+	)
+}
+
+/// Fraction of vertices whose mirror image across the plane meets another vertex
+fn mirror_match(
+	grid: &PointGrid,
+	vertices: &[Vector3<f32>],
+	center: Vector3<f32>,
+	normal: Vector3<f32>,
+) -> f32 {
+	let normal = normal.normalize();
+	let matched = vertices
+		.iter()
+		.filter(|&&v| grid.any_within(v - normal * (2.0 * (v - center).dot(&normal))))
+		.count();
+	matched as f32 / vertices.len() as f32
+}
+
+fn histogram(values: &[f32], bins: usize) -> (f32, f32, Vec<usize>) {
+	let min = values.iter().cloned().fold(f32::INFINITY, f32::min);
+	let max = values.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+	let width = (max - min) / bins as f32;
+
+	let mut counts = vec![0; bins];
+	for &value in values {
+		let bin = if width > 0.0 {
+			((value - min) / width) as usize
+		} else {
+			0
+		};
+		counts[bin.min(bins - 1)] += 1;
+	}
+	(min, width, counts)
+}
+
+fn print_histogram<W: Write>(out: &mut W, values: &[f32], bins: usize) -> io::Result<()> {
+	const BAR_WIDTH: usize = 50;
+
+	let (min, width, counts) = histogram(values, bins);
+	let peak = counts.iter().cloned().max().unwrap_or(0).max(1);
+	for (i, count) in counts.iter().enumerate() {
+		let low = min + width * i as f32;
+		writeln!(
+			out,
+			"[{:>12.6}, {:>12.6}) {:>8} {}",
+			low,
+			low + width,
+			count,
+			"#".repeat(count * BAR_WIDTH / peak)
+		)?;
+	}
+	Ok(())
+}
+
+fn transform_raw_line(transformer: &dyn Transformer, words: &[&str]) -> Option<String> {
+	if words.is_empty() || !words.len().is_multiple_of(3) {
+		return None;
+	}
+	let values: Vec<f32> = words
+		.iter()
+		.map(|w| w.parse::<f32>().ok())
+		.collect::<Option<_>>()?;
+
+	let fields: Vec<String> = values
+		.chunks(3)
+		.map(|c| {
+			let output = transformer.transform(Vector3::new(c[0], c[1], c[2]));
+			format!("{} {} {}", output.x, output.y, output.z)
+		})
+		.collect();
+	Some(fields.join(" "))
+}
+
+/// Encodes a normal as a point on the octahedron unfolded into the [-1, 1] square,
+/// quantizes it to the given bits per component and decodes it back
+fn octahedral_round_trip(normal: Vector3<f32>, bits: u8) -> Vector3<f32> {
+	let n = normal / normal.abs().sum();
+	let mut p = Vector2::new(n.x, n.y);
+	if n.z < 0.0 {
+		p = Vector2::new(
+			(1.0 - n.y.abs()) * n.x.signum(),
+			(1.0 - n.x.abs()) * n.y.signum(),
+		);
+	}
+
+	// signed normalized so that 0 and the octahedron's corners are exact
+	let levels = ((1u32 << (bits - 1)) - 1) as f32;
+	let p = p.map(|c| (c * levels).round() / levels);
+
+	let mut decoded = Vector3::new(p.x, p.y, 1.0 - p.x.abs() - p.y.abs());
+	let fold = (-decoded.z).max(0.0);
+	decoded.x -= fold * decoded.x.signum();
+	decoded.y -= fold * decoded.y.signum();
+	decoded.normalize()
+}
+
+fn quantize_normal_line(words: &[&str], bits: u8) -> Option<String> {
+	if words.len() != 4 || words[0] != "vn" {
+		return None;
+	}
+	let normal = Vector3::new(
+		words[1].parse().ok()?,
+		words[2].parse().ok()?,
+		words[3].parse().ok()?,
+	);
+	if normal.abs().sum() == 0.0 {
+		return None;
+	}
+	let output = octahedral_round_trip(normal, bits);
+	Some(format!("vn {} {} {}", output.x, output.y, output.z))
+}
+
+/// Fan triangulation of an obj face, keeping each corner's texture and normal indices
+fn triangulate_face(words: &[&str]) -> Vec<String> {
+	let corners = &words[1..];
+	(1..corners.len() - 1)
+		.map(|i| format!("f {} {} {}", corners[0], corners[i], corners[i + 1]))
+		.collect()
+}
+
+/// Writes one input line with its vertices transformed
+fn write_line<W: Write>(
+	out: &mut W,
+	options: &Options,
+	transformer: &dyn Transformer,
+	vertex_index: &mut usize,
+	text_line: &str,
+) -> io::Result<()> {
+	let words: Vec<&str> = split_words(text_line, options.lenient_parse);
+
+	if let Some(layout) = &options.xyz_columns {
+		if let Some(output) = transform_xyz_line(transformer, &layout.columns, &words) {
+			return writeln!(out, "{}", output);
+		}
+	}
+
+	if options.raw {
+		if let Some(output) = transform_raw_line(transformer, &words) {
+			return writeln!(out, "{}", output);
+		}
+	}
+
+	if let Some(bits) = options.octahedral_normals {
+		if let Some(output) = quantize_normal_line(&words, bits) {
+			return writeln!(out, "{}", output);
+		}
+	}
+
+	if options.triangulate && words.len() > 4 && words[0] == "f" {
+		for triangle in triangulate_face(&words) {
+			writeln!(out, "{}", triangle)?;
+		}
+		return Ok(());
+	}
+
+	let Some(vertex) = parse_vertex(&words) else {
+		return writeln!(out, "{}", text_line);
+	};
+	let output = transformer.transform_colored(*vertex_index, vertex, parse_vertex_color(&words));
+	*vertex_index += 1;
+
+	writeln!(out, "{}", format_vertex(&words, output))
+}
+
+fn write_output<W: Write>(
+	out: &mut W,
+	options: &Options,
+	transformer: &dyn Transformer,
+	input: impl Iterator<Item = String>,
+) -> io::Result<()> {
+	if options.stamp {
+		print_stamp(out)?;
+	}
+
+	let mut vertex_index = 0;
+	let mut frame = None;
+	for (line_number, text_line) in input.enumerate() {
+		let marker = text_line
+			.trim_start()
+			.strip_prefix('#')
+			.and_then(|comment| comment.trim_start().strip_prefix("frame"));
+		match marker {
+			Some(matrix) if options.apply_frames => {
+				match parse_dcc_matrix(matrix, DccFormat::Blender) {
+					Ok(matrix) => frame = Some(matrix),
+					Err(e) => eprintln!("Ignoring frame on line {}: {}", line_number + 1, e),
+				}
+			}
+			_ => match frame {
+				Some(frame) => {
+					let framed = FramedTransformer {
+						frame,
+						inner: transformer,
+					};
+					write_line(out, options, &framed, &mut vertex_index, &text_line)?;
+				}
+				None => write_line(out, options, transformer, &mut vertex_index, &text_line)?,
+			},
+		}
+		if let Some(chunk_lines) = options.chunk_lines {
+			if (line_number + 1) % chunk_lines.get() == 0 {
+				out.flush()?;
+			}
+		}
+	}
+	out.flush()
+}
+
+/// Writes the output, encoded as base64 if requested
+fn write_encoded<W: Write>(
+	mut out: W,
+	options: &Options,
+	transformer: &dyn Transformer,
+	input: impl Iterator<Item = String>,
+) -> io::Result<()> {
+	if options.base64 {
+		let mut out = Base64Writer::new(out);
+		write_output(&mut out, options, transformer, input).and_then(|_| out.finish())
+	} else {
+		write_output(&mut out, options, transformer, input)
+	}
+}
+
+/// Encodes everything written to it as standard padded base64
+struct Base64Writer<W: Write> {
+	inner: W,
+	pending: Vec<u8>,
+}
+
+impl<W: Write> Base64Writer<W> {
+	const ALPHABET: &'static [u8; 64] =
+		b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+	fn new(inner: W) -> Self {
+		Base64Writer {
+			inner,
+			pending: Vec::new(),
+		}
+	}
+
+	fn encode(chunk: &[u8]) -> [u8; 4] {
+		let bytes = [0, 1, 2].map(|i| chunk.get(i).copied().unwrap_or(0) as u32);
+		let group = bytes[0] << 16 | bytes[1] << 8 | bytes[2];
+		let mut encoded =
+			[0, 1, 2, 3].map(|i| Self::ALPHABET[(group >> (18 - 6 * i) & 63) as usize]);
+		for c in encoded.iter_mut().skip(chunk.len() + 1) {
+			*c = b'=';
+		}
+		encoded
+	}
+
+	/// Writes the padded final group and flushes
+	fn finish(mut self) -> io::Result<()> {
+		if !self.pending.is_empty() {
+			self.inner.write_all(&Self::encode(&self.pending))?;
+		}
+		self.inner.flush()
+	}
+}
+
+impl<W: Write> Write for Base64Writer<W> {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		self.pending.extend_from_slice(buf);
+		let complete = self.pending.len() / 3 * 3;
+		let encoded: Vec<u8> = self.pending[..complete]
+			.chunks(3)
+			.flat_map(Self::encode)
+			.collect();
+		self.inner.write_all(&encoded)?;
+		self.pending.drain(..complete);
+		Ok(buf.len())
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		self.inner.flush()
+	}
+}
+
+/// Condition numbers above this cost more than three of the seven or so digits of an f32
+const CONDITION_WARNING: f32 = 1e3;
+
+/// Ratio of the largest to the smallest singular value of the linear part
+fn condition_number(matrix: &Matrix4<f32>) -> f32 {
+	let singular_values = matrix
+		.fixed_view::<3, 3>(0, 0)
+		.into_owned()
+		.singular_values();
+	singular_values.max() / singular_values.min()
+}
+
+fn python_snippet(matrix: &Matrix4<f32>) -> String {
+	let rows: Vec<String> = matrix
+		.row_iter()
+		.map(|row| {
+			let values: Vec<String> = row.iter().map(|v| format!("{:?}", v)).collect();
+			format!("    [{}],", values.join(", "))
+		})
+		.collect();
+	format!(
+		"import numpy as np
+
+MATRIX = np.array([
+{}
+])
+
+
+def transform(points):
+    \"\"\"Applies MATRIX to an Nx3 array of points.\"\"\"
+    points = np.asarray(points, dtype=float)
+    homogeneous = np.hstack([points, np.ones((len(points), 1))])
+    return (homogeneous @ MATRIX.T)[:, :3]
+",
+		rows.join("\n")
+	)
+}
+
+fn print_stamp<W: Write>(out: &mut W) -> io::Result<()> {
+	let command: Vec<String> = std::env::args().collect();
+	let timestamp = SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map(|d| d.as_secs())
+		.unwrap_or(0);
+	writeln!(
+		out,
+		"# {} {}",
+		env!("CARGO_PKG_NAME"),
+		env!("CARGO_PKG_VERSION")
+	)?;
+	writeln!(out, "# command: {}", command.join(" "))?;
+	writeln!(out, "# timestamp: {}", timestamp)
+}
+
+/// Builds the transformer of a command that transforms each vertex independently
+fn build_transformer(command: Commands, lenient: bool) -> Result<Box<dyn Transformer>, String> {
+	let vector = |s: &str| convert(s, lenient, parse_vector3);
+	let transformer: Box<dyn Transformer> = match command {
+		Commands::Rotate { axis, angle } => Box::new(RotateTransformer {
+			axis: vector(&axis)?,
+			angle,
+		}),
+		Commands::Translate { translation } => Box::new(TranslateTransformer {
+			xyz: vector(&translation)?,
+		}),
+		Commands::Scale { scale } => Box::new(ScaleTransformer {
+			xyz: vector(&scale)?,
+		}),
+		Commands::ScaleOriented { axis, angle, scale } => {
+			let (axis, scale) = (vector(&axis)?, vector(&scale)?);
+			let Some(axis) = Unit::try_new(axis, 0.0) else {
+				return Err("The frame axis must not be zero.".to_string());
+			};
+			let frame = Rotation3::from_axis_angle(&axis, angle);
+			let linear = frame.matrix() * Matrix3::from_diagonal(&scale) * frame.inverse().matrix();
+			Box::new(AffineTransformer {
+				matrix: linear.to_homogeneous(),
+			})
+		}
+		Commands::Units { from, to } => Box::new(ScaleTransformer {
+			xyz: Vector3::repeat((from / to) as f32),
+		}),
+		Commands::DualQuat { isometry } => Box::new(RigidTransformer { isometry }),
+		Commands::Warp {
+			lines,
+			lines_file,
+			merge_collinear,
+			pre,
+			post,
+			..
+		} => {
+			let affine = |spec: Option<String>| -> Result<Option<Box<dyn Transformer>>, String> {
+				let Some(spec) = spec else {
+					return Ok(None);
+				};
+				let transformer = parse_transform(&spec, lenient)?;
+				match transformer.affine() {
+					Some(_) => Ok(Some(transformer)),
+					None => Err(format!("Not an affine transform: {}", spec)),
+				}
+			};
+			let (pre, post) = (affine(pre)?, affine(post)?);
+			let mut lines = convert_all(&lines, lenient, parse_line)?;
+			if let Some(path) = lines_file {
+				lines.extend(read_warp_lines(&path, lenient)?);
+			}
+			let given = lines.len();
+			let lines = match merge_collinear {
+				Some(angle_eps) => WarpTransformer::merge_collinear(lines, angle_eps),
+				None => lines,
+			};
+			let warp: Box<dyn Transformer> = match lines.len() {
+				// every line moves onto the first, so lines merged into one warp nothing
+				1 if given > 1 => Box::new(AffineTransformer {
+					matrix: Matrix4::identity(),
+				}),
+				_ => Box::new(WarpTransformer::new(match lines.len() {
+					0 => vec![
+						Line {
+							origin: Vector3::new(0f32, 0f32, 0f32),
+							heading: Vector3::new(1f32, 0f32, 0f32),
+						},
+						Line {
+							origin: Vector3::new(0f32, 0f32, 0f32),
+							heading: Vector3::new(0f32, 0f32, 1f32),
+						},
+					],
+					1 => {
+						return Err("A minimum of two lines is required.".to_string());
+					}
+					_ => lines,
+				})),
+			};
+			Box::new(ComposedTransformer {
+				steps: pre.into_iter().chain([warp]).chain(post).collect(),
+			})
+		}
+		Commands::Wave {
+			direction,
+			displacement,
+			amplitude,
+			wavelength,
+			phase,
+		} => {
+			let (direction, displacement) = (vector(&direction)?, vector(&displacement)?);
+			if direction.norm() == 0.0 || displacement.norm() == 0.0 {
+				return Err("The wave direction and displacement must not be zero.".to_string());
+			}
+			if wavelength <= 0.0 {
+				return Err("The wavelength must be positive.".to_string());
+			}
+			Box::new(WaveTransformer {
+				direction,
+				displacement,
+				amplitude,
+				wavelength,
+				phase,
+			})
+		}
+		Commands::Track { previous, current } => {
+			let (previous, current) = match (
+				read_vertices_file(&previous, lenient),
+				read_vertices_file(&current, lenient),
+			) {
+				(Ok(previous), Ok(current)) => (previous, current),
+				(Err(e), _) | (_, Err(e)) => {
+					return Err(format!("Failed to read frame: {}", e));
+				}
+			};
+			if previous.len() != current.len() || previous.len() < 3 {
+				return Err(
+					"Frames must have the same number of vertices, at least three.".to_string(),
+				);
+			}
+			Box::new(RigidTransformer::kabsch(&previous, &current))
+		}
+		Commands::Tps {
+			from_points,
+			to_points,
+		} => {
+			let from = read_points_file(&from_points, lenient)
+				.map_err(|e| format!("Failed to read points: {}", e))?;
+			let to = read_points_file(&to_points, lenient)
+				.map_err(|e| format!("Failed to read points: {}", e))?;
+			if from.len() != to.len() || from.len() < 4 {
+				return Err(
+					"Point sets must have the same number of points, at least four.".to_string(),
+				);
+			}
+			Box::new(TpsTransformer::new(&from, &to)?)
+		}
+		Commands::FakeDepth {
+			camera,
+			look,
+			strength,
+		} => {
+			let camera = vector(&camera)?;
+			let look = match look {
+				Some(look) => vector(&look)?,
+				None => -camera,
+			};
+			let Some(look) = look.try_normalize(0.0) else {
+				return Err("The view direction must not be zero.".to_string());
+			};
+			if strength.is_nan() || strength < 0.0 {
+				return Err("The strength must not be negative.".to_string());
+			}
+			Box::new(FakeDepthTransformer {
+				camera,
+				look,
+				strength,
+			})
+		}
+		Commands::Sweep { axis, keys } => {
+			let (axis, keys) = (vector(&axis)?, convert_all(&keys, lenient, parse_keyframe)?);
+			if keys.is_empty() {
+				return Err("A minimum of one keyframe is required.".to_string());
+			}
+			if axis.norm() == 0.0 {
+				return Err("The sweep axis must not be zero.".to_string());
+			}
+			Box::new(SweepTransformer::new(axis, keys))
+		}
+		Commands::Matrix { from_dcc, format } => {
+			let text = std::fs::read_to_string(&from_dcc)
+				.map_err(|e| format!("Failed to read matrix: {}", e))?;
+			let matrix = parse_dcc_matrix(&text, format)?;
+			Box::new(AffineTransformer { matrix })
+		}
+		Commands::UpCorrect { measured_up } => {
+			let measured_up = vector(&measured_up)?;
+			if measured_up.norm() == 0.0 {
+				return Err("The measured up vector must not be zero.".to_string());
+			}
+			let rotation = minimal_rotation(measured_up, Vector3::z());
+			Box::new(RigidTransformer {
+				isometry: Isometry3::from_parts(Translation3::identity(), rotation.into()),
+			})
+		}
+		_ => return Err("Only per vertex transforms can be used here.".to_string()),
+	};
+	Ok(transformer)
+}
+
+fn read_input<R: BufRead>(input: R) -> Result<Vec<String>, String> {
+	read_lines(input).map_err(|e| format!("Failed to read input: {}", e))
+}
+
+fn main() {
+	let Args { command, options } = Args::parse();
+	if let Err(e) = run(command, &options, io::stdin().lock(), io::stdout().lock()) {
+		eprintln!("{}", e);
+		std::process::exit(1);
+	}
+}
+
+/// Runs a command over the obj or stl lines of input, writing the result to out
+fn run<R: BufRead, W: Write>(
+	command: Commands,
+	options: &Options,
+	mut input: R,
+	mut out: W,
+) -> Result<(), String> {
+	let lenient = options.lenient_parse;
+	let vector = |s: &str| convert(s, lenient, parse_vector3);
+	let reads_whole_input = command.reads_whole_input()
+		|| options.largest_component_only
+		|| options.relax_area.is_some();
+	if options.chunk_lines.is_some() && reads_whole_input {
+		return Err(
+			"--chunk-lines cannot be used with commands that read the whole input.".to_string(),
+		);
+	}
+	if options.apply_frames && reads_whole_input {
+		return Err(
+			"--apply-frames cannot be used with commands that read the whole input.".to_string(),
+		);
+	}
+
+	let mut buffered: Option<Vec<String>> = None;
+	let jacobian_points = match &command {
+		Commands::Warp { warp_jacobian, .. } => convert_all(warp_jacobian, lenient, parse_vector3)?,
+		_ => Vec::new(),
+	};
+
+	let transformer: Box<dyn Transformer> = match command {
+		Commands::EdgeAlign { to } => {
+			let to = vector(&to)?;
+			if to.norm() == 0.0 {
+				return Err("The target direction must not be zero.".to_string());
+			}
+			let text = read_input(&mut input)?;
+			let mesh = Mesh::parse(&text, lenient);
+			let longest = mesh.edges().into_iter().max_by(|&(a, b), &(c, d)| {
+				let ab = (mesh.vertices[b] - mesh.vertices[a]).norm();
+				let cd = (mesh.vertices[d] - mesh.vertices[c]).norm();
+				ab.total_cmp(&cd)
+			});
+			let Some((a, b)) = longest else {
+				return Err("Aligning an edge requires faces.".to_string());
+			};
+			buffered = Some(text);
+			let rotation = minimal_rotation(mesh.vertices[b] - mesh.vertices[a], to);
+			Box::new(RigidTransformer {
+				isometry: Isometry3::from_parts(Translation3::identity(), rotation.into()),
+			})
+		}
+		Commands::SurfaceFalloff {
+			seed_vertex,
+			radius,
+			offset,
+		} => {
+			let offset = vector(&offset)?;
+			let text = read_input(&mut input)?;
+			let mesh = Mesh::parse(&text, lenient);
+			if seed_vertex >= mesh.vertices.len() {
+				return Err(format!("Seed vertex {} does not exist.", seed_vertex));
+			}
+			if mesh.faces.is_empty() {
+				return Err("Surface falloff requires faces.".to_string());
+			}
+			if radius.is_nan() || radius <= 0.0 {
+				return Err("The falloff radius must be positive.".to_string());
+			}
+			buffered = Some(text);
+			let distances = mesh.edge_distances(seed_vertex);
+			let positions = mesh
+				.vertices
+				.iter()
+				.zip(distances)
+				.map(|(v, d)| v + offset * (1.0 - d / radius).max(0.0))
+				.collect();
+			Box::new(IndexedTransformer { positions })
+		}
+		Commands::SnapToPlanes { eps, min_vertices } => {
+			if eps.is_nan() || eps <= 0.0 {
+				return Err("The snapping distance must be positive.".to_string());
+			}
+			let text = read_input(&mut input)?;
+			let mesh = Mesh::parse(&text, lenient);
+			buffered = Some(text);
+			Box::new(SnapTransformer::new(&mesh.vertices, eps, min_vertices))
+		}
+		Commands::CurvatureDisplace { amount } => {
+			let text = read_input(&mut input)?;
+			let mesh = Mesh::parse(&text, lenient);
+			if mesh.faces.is_empty() {
+				return Err("Curvature displacement requires faces.".to_string());
+			}
+			buffered = Some(text);
+			let (curvature, normals) = mesh.mean_curvature();
+			let positions = (0..mesh.vertices.len())
+				.map(|v| mesh.vertices[v] + normals[v] * curvature[v] * amount)
+				.collect();
+			Box::new(IndexedTransformer { positions })
+		}
+		Commands::SphereNormalize { radius, center } => {
+			let text = read_input(&mut input)?;
+			let vertices = Mesh::parse(&text, lenient).vertices;
+			if vertices.is_empty() {
+				return Err("No vertices found.".to_string());
+			}
+			let center = match center {
+				Center::Centroid => vertices.iter().sum::<Vector3<f32>>() / vertices.len() as f32,
+				Center::Box => {
+					let (min, max) = bounding_box(&vertices);
+					(min + max) / 2.0
+				}
+				Center::Sphere => minimal_enclosing_sphere(&vertices).0,
+			};
+			let farthest = vertices
+				.iter()
+				.map(|v| (v - center).norm())
+				.fold(0.0, f32::max);
+			if farthest == 0.0 {
+				return Err("All vertices lie at the center.".to_string());
+			}
+			buffered = Some(text);
+			Box::new(AffineTransformer {
+				matrix: Matrix4::new_scaling(radius / farthest)
+					* Matrix4::new_translation(&-center),
+			})
+		}
+		Commands::BoundingSphere { normalize } => {
+			let text = read_input(&mut input)?;
+			let vertices = Mesh::parse(&text, lenient).vertices;
+			if vertices.is_empty() {
+				return Err("No vertices found.".to_string());
+			}
+			let (center, radius) = minimal_enclosing_sphere(&vertices);
+			if !normalize {
+				options.reject_transform_options("bounding-sphere")?;
+				writeln!(out, "center {} {} {}", center.x, center.y, center.z)
+					.and_then(|_| writeln!(out, "radius {}", radius))
+					.map_err(|e| e.to_string())?;
+				return Ok(());
+			}
+			if radius == 0.0 {
+				return Err("All vertices lie at the center.".to_string());
+			}
+			buffered = Some(text);
+			Box::new(AffineTransformer {
+				matrix: Matrix4::new_scaling(1.0 / radius) * Matrix4::new_translation(&-center),
+			})
+		}
+		Commands::FrameAlign { x, y } => {
+			let (x, y) = (vector(&x)?, vector(&y)?);
+			if (x.norm() - 1.0).abs() > FRAME_TOLERANCE
+				|| (y.norm() - 1.0).abs() > FRAME_TOLERANCE
+				|| x.dot(&y).abs() > FRAME_TOLERANCE
+			{
+				return Err("The reference frame must be two orthogonal unit vectors.".to_string());
+			}
+			let text = read_input(&mut input)?;
+			let vertices = Mesh::parse(&text, lenient).vertices;
+			if vertices.is_empty() {
+				return Err("No vertices found.".to_string());
+			}
+			let (centroid, axes) = principal_axes(&vertices);
+			let frame = Matrix3::from_columns(&[x, y, x.cross(&y)]);
+			let rotation = Rotation3::from_matrix(&(frame * axes.transpose()));
+			buffered = Some(text);
+			Box::new(RigidTransformer {
+				isometry: Isometry3::from_parts(
+					Translation3::from(centroid - rotation * centroid),
+					rotation.into(),
+				),
+			})
+		}
+		Commands::Sag {
+			pinned_axis,
+			strength,
+			gravity,
+		} => {
+			let gravity = vector(&gravity)?;
+			let text = read_input(&mut input)?;
+			let vertices = Mesh::parse(&text, lenient).vertices;
+			let axis = pinned_axis.index();
+			let (min, max) = bounding_box(&vertices);
+			if vertices.is_empty() || min[axis] == max[axis] {
+				return Err("The pinned ends must be apart along the pinned axis.".to_string());
+			}
+			buffered = Some(text);
+			Box::new(SagTransformer {
+				axis,
+				min: min[axis],
+				max: max[axis],
+				gravity: gravity.normalize(),
+				strength,
+			})
+		}
+		Commands::Ease {
+			transform,
+			steps,
+			curve,
+			output,
+		} => {
+			options.reject_transform_options("ease")?;
+			let target = parse_transform(&transform, lenient)?;
+			if steps == 0 {
+				return Err("At least one step is required.".to_string());
+			}
+			let text = read_input(&mut input)?;
+			for frame in 0..steps {
+				let t = match steps {
+					1 => 1.0,
+					_ => curve.apply(frame as f32 / (steps - 1) as f32),
+				};
+				let eased = target
+					.affine()
+					.and_then(|matrix| interpolate_affine(&matrix, t));
+				let transformer: Box<dyn Transformer> = match eased {
+					Some(matrix) => Box::new(AffineTransformer { matrix }),
+					None => Box::new(LerpTransformer {
+						inner: target.as_ref(),
+						t,
+					}),
+				};
+				let path = output.replace("{}", &frame.to_string());
+				File::create(&path)
+					.and_then(|file| {
+						let mut out = BufWriter::new(file);
+						write_output(
+							&mut out,
+							options,
+							transformer.as_ref(),
+							text.iter().cloned(),
+						)
+					})
+					.map_err(|e| format!("Failed to write {}: {}", path, e))?;
+			}
+			return Ok(());
+		}
+		Commands::SetOrigin { vertex } => {
+			let text = read_input(&mut input)?;
+			let mesh = Mesh::parse(&text, lenient);
+			let Some(origin) = mesh.vertices.get(vertex) else {
+				return Err(format!("Vertex {} does not exist.", vertex));
+			};
+			let xyz = -origin;
+			buffered = Some(text);
+			Box::new(TranslateTransformer { xyz })
+		}
+		Commands::Array {
+			count,
+			spacing,
+			step_axis,
+			step_angle,
+		} => {
+			options.reject_transform_options("array")?;
+			let (spacing, step_axis) = (vector(&spacing)?, vector(&step_axis)?);
+			if step_axis.norm() == 0.0 {
+				return Err("The step axis must not be zero.".to_string());
+			}
+			let step = RotateTransformer {
+				axis: step_axis,
+				angle: step_angle,
+			};
+			let text = read_input(&mut input)?;
+			buffered = Some(array_lines(&text, count, spacing, step, lenient));
+			Box::new(TranslateTransformer {
+				xyz: Vector3::zeros(),
+			})
+		}
+		Commands::Simplify { ratio } => {
+			options.reject_transform_options("simplify")?;
+			if ratio.is_nan() || ratio <= 0.0 || ratio > 1.0 {
+				return Err("The ratio must be greater than 0 and at most 1.".to_string());
+			}
+			let text = read_input(&mut input)?;
+			let mesh = Mesh::parse(&text, lenient);
+			if !text.iter().any(|line| line.starts_with("f ")) {
+				return Err("Simplification requires obj faces.".to_string());
+			}
+			let keywords = text.iter().map(|line| line.split_whitespace().next());
+			if keywords
+				.clone()
+				.any(|keyword| matches!(keyword, Some("vt" | "vn")))
+			{
+				eprintln!("warning: simplify drops texture coordinates and normals");
+			}
+			let triangles = mesh.face_triangles();
+			let target = (triangles.len() as f32 * ratio).ceil() as usize;
+			let corners: Vec<[usize; 3]> = triangles.iter().map(|&(_, corners)| corners).collect();
+			let (vertices, faces) = simplify(&mesh.vertices, &corners, target);
+
+			// what is left of a face replaces its line, keeping the groups and
+			// materials around it, and all vertices take the place of the first
+			let mut remaining: HashMap<usize, Vec<String>> = HashMap::new();
+			for (t, [a, b, c]) in faces {
+				let line = mesh.face_lines[triangles[t].0];
+				let face = format!("f {} {} {}", a + 1, b + 1, c + 1);
+				remaining.entry(line).or_default().push(face);
+			}
+			let mut vertex_lines = Some(
+				vertices
+					.iter()
+					.map(|v| format!("v {} {} {}", v.x, v.y, v.z)),
+			);
+			let mut output = Vec::new();
+			for (line_number, (text_line, keyword)) in text.iter().zip(keywords).enumerate() {
+				match keyword {
+					Some("v") => output.extend(vertex_lines.take().into_iter().flatten()),
+					Some("vt" | "vn" | "vp" | "f" | "l") => {}
+					_ => output.push(text_line.clone()),
+				}
+				output.extend(remaining.remove(&line_number).into_iter().flatten());
+			}
+			buffered = Some(output);
+			Box::new(TranslateTransformer {
+				xyz: Vector3::zeros(),
+			})
+		}
+		Commands::Symmetry { planes, tolerance } => {
+			options.reject_transform_options("symmetry")?;
+			let planes = convert_all(&planes, lenient, parse_vector3)?;
+			if tolerance <= 0.0 {
+				return Err("The tolerance must be positive.".to_string());
+			}
+			let vertices = read_vertices(&mut input, lenient).map_err(|e| e.to_string())?;
+			if vertices.is_empty() {
+				return Err("No vertices found.".to_string());
+			}
+			let (min, max) = bounding_box(&vertices);
+			let center = (min + max) / 2.0;
+			let grid = PointGrid::new(&vertices, tolerance);
+			let candidates = [
+				(1.0, 0.0, 0.0),
+				(0.0, 1.0, 0.0),
+				(0.0, 0.0, 1.0),
+				(1.0, 1.0, 0.0),
+				(1.0, -1.0, 0.0),
+				(0.0, 1.0, 1.0),
+				(0.0, 1.0, -1.0),
+				(1.0, 0.0, 1.0),
+				(1.0, 0.0, -1.0),
+			]
+			.map(|(x, y, z)| Vector3::new(x, y, z));
+			writeln!(out, "center {} {} {}", center.x, center.y, center.z)
+				.map_err(|e| e.to_string())?;
+			for normal in candidates.into_iter().chain(planes) {
+				let matched = mirror_match(&grid, &vertices, center, normal);
+				writeln!(
+					out,
+					"normal {},{},{} matched {:.1}% {}",
+					normal.x,
+					normal.y,
+					normal.z,
+					matched * 100.0,
+					if matched == 1.0 {
+						"symmetric"
+					} else {
+						"asymmetric"
+					}
+				)
+				.map_err(|e| e.to_string())?;
+			}
+			return Ok(());
+		}
+		Commands::Footprint {
+			plane: (axis, offset),
+			light,
+			point_light,
+			outline,
+		} => {
+			let axis = axis.index();
+			let light = light.map(|light| vector(&light)).transpose()?;
+			let light = match (light, point_light) {
+				(None, true) => {
+					return Err("--point-light requires --light.".to_string());
+				}
+				(None, false) => Light::Directional(Vector3::ith(axis, 1.0)),
+				(Some(direction), false) if direction[axis] == 0.0 => {
+					return Err(
+						"The light direction must not be parallel to the plane.".to_string()
+					);
+				}
+				(Some(direction), false) => Light::Directional(direction),
+				(Some(position), true) => Light::Point(position),
+			};
+			let footprint = FootprintTransformer {
+				axis,
+				offset,
+				light,
+			};
+			if outline {
+				options.reject_transform_options("footprint --outline")?;
+				let vertices = read_vertices(&mut input, lenient).map_err(|e| e.to_string())?;
+				let projected: Vec<Vector3<f32>> =
+					vertices.iter().map(|&v| footprint.transform(v)).collect();
+				let hull = convex_outline(&projected, axis);
+				if hull.len() < 3 {
+					return Err("The outline needs vertices spanning an area.".to_string());
+				}
+				let mut text: Vec<String> = hull
+					.iter()
+					.map(|p| format!("v {} {} {}", p.x, p.y, p.z))
+					.collect();
+				let corners: Vec<String> = (1..=hull.len()).map(|i| i.to_string()).collect();
+				text.push(format!("f {}", corners.join(" ")));
+				buffered = Some(text);
+				Box::new(TranslateTransformer {
+					xyz: Vector3::zeros(),
+				})
+			} else {
+				Box::new(footprint)
+			}
+		}
+		Commands::Histogram { axis, bins } => {
+			options.reject_transform_options("histogram")?;
+			if bins == 0 {
+				return Err("At least one bin is required.".to_string());
+			}
+			let vertices = read_vertices(&mut input, lenient).map_err(|e| e.to_string())?;
+			if vertices.is_empty() {
+				return Err("No vertices found.".to_string());
+			}
+			let values: Vec<f32> = vertices.iter().map(|v| v[axis.index()]).collect();
+			print_histogram(&mut out, &values, bins).map_err(|e| e.to_string())?;
+			return Ok(());
+		}
+		command => build_transformer(command, lenient)?,
+	};
+
+	if !jacobian_points.is_empty() {
+		options.reject_transform_options("--warp-jacobian")?;
+		for pt in jacobian_points {
+			let jacobian = transformer.jacobian(pt);
+			writeln!(out, "jacobian at {},{},{}", pt.x, pt.y, pt.z).map_err(|e| e.to_string())?;
+			for row in jacobian.row_iter() {
+				writeln!(out, "{} {} {}", row[0], row[1], row[2]).map_err(|e| e.to_string())?;
+			}
+			writeln!(out, "determinant {}", jacobian.determinant()).map_err(|e| e.to_string())?;
+		}
+		return Ok(());
+	}
+
+	let transformer: Box<dyn Transformer> = match (options.orthogonalize, transformer.affine()) {
+		(false, _) => transformer,
+		(true, Some(matrix)) => Box::new(AffineTransformer {
+			matrix: AffineTransformer::orthogonalize(matrix),
+		}),
+		(true, None) => {
+			return Err("--orthogonalize requires an affine transform.".to_string());
+		}
+	};
+
+	if options.emit_python {
+		let Some(matrix) = transformer.affine() else {
+			return Err("--emit-python only supports affine transforms; non linear transforms such as warp cannot be written as a matrix.".to_string());
+		};
+		write!(out, "{}", python_snippet(&matrix)).map_err(|e| e.to_string())?;
+		return Ok(());
+	}
+
+	if options.condition {
+		let Some(matrix) = transformer.affine() else {
+			return Err("--condition requires an affine transform.".to_string());
+		};
+		let condition = condition_number(&matrix);
+		eprintln!("condition number: {}", condition);
+		if condition > CONDITION_WARNING {
+			eprintln!(
+				"warning: the transform is close to degenerate and will amplify rounding error"
+			);
+		}
+	}
+
+	let transformer: Box<dyn Transformer> = match &options.by_color {
+		Some(path) => {
+			let entries = ColorTransformer::read_manifest(path, lenient)?;
+			Box::new(ColorTransformer {
+				entries,
+				tolerance: options.color_tolerance,
+				inner: transformer,
+			})
+		}
+		None => transformer,
+	};
+
+	let transformer: Box<dyn Transformer> = match options.largest_component_only {
+		true => {
+			let text = match buffered.take() {
+				Some(text) => text,
+				None => read_input(&mut input)?,
+			};
+			let components = Mesh::parse(&text, lenient).components();
+			let mut sizes: HashMap<usize, usize> = HashMap::new();
+			for &component in &components {
+				*sizes.entry(component).or_insert(0) += 1;
+			}
+			let largest = sizes
+				.into_iter()
+				.max_by_key(|&(component, size)| (size, Reverse(component)));
+			let mask = components
+				.iter()
+				.map(|&component| Some(component) == largest.map(|(c, _)| c))
+				.collect();
+			buffered = Some(text);
+			Box::new(MaskTransformer {
+				mask,
+				inner: transformer,
+			})
+		}
+		false => transformer,
+	};
+
+	let transformer: Box<dyn Transformer> = match options.relax_area {
+		Some(iterations) => {
+			let text = match buffered.take() {
+				Some(text) => text,
+				None => read_input(&mut input)?,
+			};
+			let positions = Mesh::parse(&text, lenient).relax_area(iterations);
+			buffered = Some(text);
+			Box::new(RepositionTransformer {
+				positions,
+				inner: transformer,
+			})
+		}
+		None => transformer,
+	};
+
+	let transformer: Box<dyn Transformer> = match options.max_displacement {
+		Some(max_displacement) => Box::new(ClampTransformer {
+			inner: transformer,
+			max_displacement,
+		}),
+		None => transformer,
+	};
+
+	let samples = Rc::new(RefCell::new(Vec::new()));
+	let transformer: Box<dyn Transformer> = match options.profile_transform {
+		true => Box::new(ProfiledTransformer {
+			inner: transformer,
+			vertices: Cell::new(0),
+			samples: Rc::clone(&samples),
+		}),
+		false => transformer,
+	};
+
+	let input: Box<dyn Iterator<Item = String>> = match buffered {
+		Some(text) => Box::new(text.into_iter()),
+		None => Box::new(input.lines().map(|text_line| text_line.unwrap())),
+	};
+	// without chunks every line is flushed as it is written
+	let written = match options.chunk_lines {
+		Some(_) => write_encoded(BufWriter::new(out), options, transformer.as_ref(), input),
+		None => write_encoded(LineWriter::new(out), options, transformer.as_ref(), input),
+	};
+	written.map_err(|e| e.to_string())?;
+
+	if options.profile_transform {
+		eprintln!("{}", profile_summary(&mut samples.borrow_mut()));
+	}
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn translate_reads_vector_from_environment() {
+		std::env::set_var("MESHTRANSFORM_TRANSLATE", "1,2,3");
+		let args = Args::try_parse_from(["mesh_transform", "translate"]).unwrap();
+		let Commands::Translate { translation } = &args.command else {
+			panic!("expected translate");
+		};
+		assert_eq!(translation, "1,2,3");
+		let translate = build_transformer(args.command, false).unwrap();
+		assert_eq!(
+			translate.transform(Vector3::zeros()),
+			Vector3::new(1.0, 2.0, 3.0)
+		);
+	}
+
+	fn transformer(args: &[&str]) -> Result<Box<dyn Transformer>, String> {
+		let args = Args::try_parse_from(["mesh_transform"].iter().chain(args)).unwrap();
+		build_transformer(args.command, args.options.lenient_parse)
+	}
+
+	fn assert_near(a: Vector3<f32>, b: Vector3<f32>) {
+		assert!((a - b).norm() < 1e-4, "{} is not near {}", a, b);
+	}
+
+	fn run_args(args: &[&str], input: &str) -> Result<String, String> {
+		let args = Args::try_parse_from(["mesh_transform"].iter().chain(args))
+			.map_err(|e| e.to_string())?;
+		let mut out = Vec::new();
+		run(args.command, &args.options, input.as_bytes(), &mut out)?;
+		Ok(String::from_utf8(out).unwrap())
+	}
+
+	#[test]
+	fn wave_follows_sine_formula() {
+		let wave = transformer(&[
+			"wave",
+			"--direction",
+			"1,0,0",
+			"--amplitude",
+			"2",
+			"--wavelength",
+			"4",
+			"--phase",
+			"0.5",
+		])
+		.unwrap();
+		for x in [0.0, 0.3, 1.0, 2.5, -3.0] {
+			let pt = Vector3::new(x, 1.0, 0.0);
+			let offset = 2.0 * (2.0 * std::f32::consts::PI * x / 4.0 + 0.5).sin();
+			assert_near(wave.transform(pt), pt + Vector3::z() * offset);
+		}
+	}
+
+	#[test]
+	fn wave_with_zero_amplitude_is_identity() {
+		let wave = transformer(&[
+			"wave",
+			"--direction",
+			"1,1,0",
+			"--amplitude",
+			"0",
+			"--wavelength",
+			"3",
+		])
+		.unwrap();
+		let pt = Vector3::new(0.7, -1.2, 3.0);
+		assert_eq!(wave.transform(pt), pt);
+	}
+
+	#[test]
+	fn wave_rejects_degenerate_inputs() {
+		let wave = |direction, displacement, wavelength| {
+			transformer(&[
+				"wave",
+				"--direction",
+				direction,
+				"--displacement",
+				displacement,
+				"--amplitude",
+				"1",
+				"--wavelength",
+				wavelength,
+			])
+		};
+		assert!(wave("1,0,0", "0,0,1", "0").is_err());
+		assert!(wave("0,0,0", "0,0,1", "1").is_err());
+		assert!(wave("1,0,0", "0,0,0", "1").is_err());
+		assert!(wave("1,0,0", "0,0,1", "1").is_ok());
+	}
+
+	const NEARLY_COLLINEAR: [&str; 6] = [
+		"--line",
+		"0,0,0 1,0,0",
+		"--line",
+		"0,1,0 1,1.01,0",
+		"--line",
+		"0,2,0 1,1.99,0",
+	];
+
+	#[test]
+	fn warp_merges_nearly_collinear_lines_into_one() {
+		let lines = ["0,0,0 1,0,0", "0,1,0 1,1.01,0", "0,2,0 1,1.99,0"]
+			.map(|s| parse_line(s, false).unwrap());
+		let merged = WarpTransformer::merge_collinear(lines.to_vec(), 0.05);
+		assert_eq!(merged.len(), 1);
+		assert_near(merged[0].origin, Vector3::new(0.0, 1.0, 0.0));
+		assert!((merged[0].heading - merged[0].origin).normalize().x > 0.999);
+	}
+
+	#[test]
+	fn warp_of_lines_merged_into_one_leaves_vertices_in_place() {
+		let args: Vec<&str> = ["warp"]
+			.into_iter()
+			.chain(NEARLY_COLLINEAR)
+			.chain(["--merge-collinear", "0.05"])
+			.collect();
+		let output = run_args(&args, "v 1 2 3\nv -4 0.5 2\n").unwrap();
+		assert_eq!(output, "v 1 2 3\nv -4 0.5 2\n");
+	}
+
+	#[test]
+	fn errors_are_returned_rather_than_printed() {
+		assert!(run_args(&["warp", "--line", "0,0,0 1,0,0"], "v 0 0 0\n").is_err());
+	}
+
+	/// Numbers of each line of some output
+	fn output_vertices(output: &str) -> Vec<Vector3<f32>> {
+		output
+			.lines()
+			.filter_map(|text_line| parse_vertex(&split_words(text_line, false)))
+			.collect()
+	}
+
+	fn output_numbers(output: &str) -> Vec<Vec<f32>> {
+		output
+			.lines()
+			.map(|text_line| {
+				split_words(text_line, false)
+					.iter()
+					.filter_map(|word| word.parse().ok())
+					.collect()
+			})
+			.collect()
+	}
+
+	#[test]
+	fn xyz_with_normals_round_trips_through_a_rotation() {
+		let input = "1 2 3 1 0 0 9\n-0.5 0 4 0 0.6 0.8 7\n";
+		let layout = ["--xyz-columns", "x,y,z,nx,ny,nz,other"];
+		let rotate = |angle: &str, input: &str| {
+			let args: Vec<&str> = ["rotate", "0,0,1", angle]
+				.into_iter()
+				.chain(layout)
+				.collect();
+			run_args(&args, input).unwrap()
+		};
+
+		let rotated = rotate("1.5707964", input);
+		let first = &output_numbers(&rotated)[0];
+		assert_near(
+			Vector3::new(first[0], first[1], first[2]),
+			Vector3::new(-2.0, 1.0, 3.0),
+		);
+		assert_near(Vector3::new(first[3], first[4], first[5]), Vector3::y());
+		assert_eq!(first[6], 9.0);
+
+		let restored = rotate("-1.5707964", &rotated);
+		for (output, expected) in output_numbers(&restored).iter().zip(output_numbers(input)) {
+			assert_eq!(output.len(), expected.len());
+			for (a, b) in output.iter().zip(&expected) {
+				assert!(
+					(a - b).abs() < 1e-4,
+					"{:?} is not near {:?}",
+					output,
+					expected
+				);
+			}
+		}
+	}
+
+	#[test]
+	fn kabsch_recovers_a_known_incremental_motion() {
+		let motion = Isometry3::new(Vector3::new(0.3, -1.0, 2.0), Vector3::y() * 0.2);
+		let previous = [
+			Vector3::new(0.0, 0.0, 0.0),
+			Vector3::new(1.0, 0.0, 0.0),
+			Vector3::new(0.0, 2.0, 0.0),
+			Vector3::new(0.0, 0.0, 3.0),
+			Vector3::new(1.0, 1.0, 1.0),
+		];
+		let current: Vec<Vector3<f32>> = previous
+			.iter()
+			.map(|&p| (motion * Point3::from(p)).coords)
+			.collect();
+
+		let recovered = RigidTransformer::kabsch(&previous, &current);
+		let difference = recovered.isometry.to_homogeneous() - motion.to_homogeneous();
+		assert!(difference.abs().max() < 1e-5, "{}", difference);
+	}
+
+	#[test]
+	fn raw_transforms_bare_coordinate_triples() {
+		let input = "1 2 3\n4 5 6 7 8 9\n# not numbers\n1 2\n";
+		let output = run_args(&["--raw", "translate", "1,0,-1"], input).unwrap();
+		assert_eq!(output, "2 2 2\n5 5 5 8 8 8\n# not numbers\n1 2\n");
+	}
+
+	#[test]
+	fn max_displacement_clamps_to_exactly_d() {
+		let clamp = |translation: &str, input: &str| {
+			run_args(
+				&["--max-displacement", "2.5", "translate", translation],
+				input,
+			)
+			.unwrap()
+		};
+		assert_eq!(clamp("10,0,0", "v 1 1 1\n"), "v 3.5 1 1\n");
+		let moved = output_numbers(&clamp("3,4,0", "v 0 0 0\n"))[0].clone();
+		assert_eq!(Vector3::new(moved[0], moved[1], moved[2]).norm(), 2.5);
+		assert_eq!(clamp("1,0,0", "v 0 0 0\n"), "v 1 0 0\n");
+	}
+
+	#[test]
+	fn max_displacement_must_not_be_negative() {
+		let args = [
+			"mesh_transform",
+			"--max-displacement",
+			"-1",
+			"translate",
+			"1,0,0",
+		];
+		assert!(Args::try_parse_from(args).is_err());
+	}
+
+	/// Writes a file for a test to read, named uniquely per process
+	fn temp_file(name: &str, contents: &str) -> PathBuf {
+		let path =
+			std::env::temp_dir().join(format!("mesh_transform_{}_{}", std::process::id(), name));
+		std::fs::write(&path, contents).unwrap();
+		path
+	}
+
+	#[test]
+	fn histogram_counts_a_known_distribution() {
+		let values = [0.0, 0.5, 1.0, 1.5, 2.0, 2.5, 3.0, 3.9, 4.0, 4.0];
+		let (min, width, counts) = histogram(&values, 4);
+		assert_eq!((min, width), (0.0, 1.0));
+		assert_eq!(counts, vec![2, 2, 2, 4]);
+	}
+
+	#[test]
+	fn histogram_prints_one_row_per_bin() {
+		let output = run_args(
+			&["histogram", "--axis", "x", "--bins", "3"],
+			"v 0 0 0\nv 0.5 5 5\nv 3 5 5\n",
+		);
+		let rows: Vec<String> = output.unwrap().lines().map(str::to_string).collect();
+		assert_eq!(rows.len(), 3);
+		let counts: Vec<&str> = rows
+			.iter()
+			.map(|row| {
+				row.split(')')
+					.nth(1)
+					.unwrap()
+					.split_whitespace()
+					.next()
+					.unwrap()
+			})
+			.collect();
+		assert_eq!(counts, ["2", "0", "1"]);
+	}
+
+	#[test]
+	fn track_frames_with_a_malformed_vertex_are_rejected() {
+		let previous = temp_file("track_previous.obj", "v 0 0 0\nv 1 0 0\nv 0 1 0\n");
+		let current = temp_file("track_current.obj", "v 0 0 0\nv 1 x 0\nv 0 1 0\n");
+		assert_eq!(
+			read_vertices_file(&current, false).unwrap_err().kind(),
+			io::ErrorKind::InvalidData
+		);
+		let args = [
+			"track",
+			"--previous",
+			previous.to_str().unwrap(),
+			"--current",
+			current.to_str().unwrap(),
+		];
+		assert!(run_args(&args, "v 0 0 0\n").is_err());
+	}
+
+	#[test]
+	fn sweep_smoothsteps_between_two_keyframes() {
+		let sweep = transformer(&[
+			"sweep",
+			"--axis",
+			"0,0,1",
+			"--key",
+			"0 0",
+			"--key",
+			"2 1.5707964 4,0,0",
+		])
+		.unwrap();
+		assert_near(
+			sweep.transform(Vector3::new(1.0, 0.0, -1.0)),
+			Vector3::new(1.0, 0.0, -1.0),
+		);
+		assert_near(
+			sweep.transform(Vector3::new(1.0, 0.0, 3.0)),
+			Vector3::new(4.0, 1.0, 3.0),
+		);
+		// halfway along smoothstep is also halfway: 45 degrees and half the offset
+		let half = std::f32::consts::FRAC_1_SQRT_2;
+		assert_near(
+			sweep.transform(Vector3::new(1.0, 0.0, 1.0)),
+			Vector3::new(half + 2.0, half, 1.0),
+		);
+		// a quarter of the way is eased to 5/32 of the offset
+		assert_near(
+			sweep.transform(Vector3::new(0.0, 0.0, 0.5)),
+			Vector3::new(0.625, 0.0, 0.5),
+		);
+	}
+
+	#[test]
+	fn sweep_of_a_nan_vertex_does_not_panic() {
+		let sweep =
+			transformer(&["sweep", "--axis", "0,0,1", "--key", "0 0", "--key", "1 1"]).unwrap();
+		assert!(sweep
+			.transform(Vector3::new(0.0, 0.0, f32::NAN))
+			.iter()
+			.all(|c| c.is_nan()));
+		assert!(transformer(&["sweep", "--axis", "0,0,0", "--key", "0 0"]).is_err());
+		assert!(parse_keyframe("NaN 0", false).is_err());
+	}
+
+	#[test]
+	fn edge_align_leaves_longest_edge_parallel_to_target() {
+		let input = "v 0 0 0\nv 4 0 0\nv 2 1 0\nf 1 2 3\n";
+		let output = run_args(&["edge-align", "--to", "0,1,1"], input).unwrap();
+		let vertices = output_vertices(&output);
+		assert_eq!(vertices.len(), 3);
+		let edge = vertices[1] - vertices[0];
+		assert!((edge.norm() - 4.0).abs() < 1e-4);
+		assert!(
+			edge.normalize()
+				.cross(&Vector3::new(0.0, 1.0, 1.0).normalize())
+				.norm() < 1e-4
+		);
+	}
+
+	#[test]
+	fn stamp_precedes_the_first_vertex_and_records_the_command() {
+		let output = run_args(&["translate", "1,0,0", "--stamp"], "# source\nv 0 0 0\n").unwrap();
+		let lines: Vec<&str> = output.lines().collect();
+		let first_vertex = lines
+			.iter()
+			.position(|text_line| text_line.starts_with("v "))
+			.unwrap();
+		let stamp = &lines[..first_vertex];
+		assert!(stamp.iter().all(|text_line| text_line.starts_with('#')));
+		assert!(stamp[0].contains(env!("CARGO_PKG_VERSION")));
+		let program = std::env::args().next().unwrap();
+		assert!(stamp
+			.iter()
+			.any(|text_line| text_line.starts_with("# command: ") && text_line.contains(&program)));
+		assert!(stamp
+			.iter()
+			.any(|text_line| text_line.starts_with("# timestamp: ")));
+		assert_eq!(lines[first_vertex], "v 1 0 0");
+	}
+
+	#[test]
+	fn orthogonalize_snaps_a_nearly_right_angle_exactly() {
+		let angle = 87f32.to_radians().to_string();
+		let args = Args::try_parse_from([
+			"mesh_transform",
+			"--orthogonalize",
+			"rotate",
+			"0,0,1",
+			&angle,
+		])
+		.unwrap();
+		let rotation = build_transformer(args.command, args.options.lenient_parse)
+			.unwrap()
+			.affine()
+			.unwrap();
+		let snapped = AffineTransformer::orthogonalize(rotation);
+		#[rustfmt::skip]
+		let expected = Matrix4::new(
+			0.0, -1.0, 0.0, 0.0,
+			1.0, 0.0, 0.0, 0.0,
+			0.0, 0.0, 1.0, 0.0,
+			0.0, 0.0, 0.0, 1.0,
+		);
+		assert_eq!(snapped, expected);
+		let output =
+			run_args(&["--orthogonalize", "rotate", "0,0,1", &angle], "v 1 0 0\n").unwrap();
+		assert_eq!(output, "v 0 1 0\n");
+	}
+
+	#[test]
+	fn orthogonalize_keeps_a_non_uniform_stretch() {
+		let scale = Matrix4::new_nonuniform_scaling(&Vector3::new(2.0, 3.0, 4.0));
+		let snapped = AffineTransformer::orthogonalize(scale);
+		assert!((snapped - scale).abs().max() < 1e-5);
+	}
+
+	#[test]
+	fn blender_matrix_is_read_as_printed() {
+		let printed = "<Matrix 4x4 (1.0000, 0.0000, 0.0000, 1.0000)\n\
+		               (0.0000, 0.0000, -1.0000, 2.0000)\n\
+		               (0.0000, 1.0000, 0.0000, 3.0000)\n\
+		               (0.0000, 0.0000, 0.0000, 1.0000)>";
+		let matrix = parse_dcc_matrix(printed, DccFormat::Blender).unwrap();
+		let moved = matrix.transform_point(&Point3::new(1.0, 1.0, 0.0)).coords;
+		assert_near(moved, Vector3::new(2.0, 2.0, 4.0));
+	}
+
+	#[test]
+	fn maya_matrix_is_transposed_and_turned_z_up() {
+		let path = temp_file("maya_matrix.txt", "1 0 0 0\n0 1 0 0\n0 0 1 0\n1 2 3 1\n");
+		let args = [
+			"matrix",
+			"--from-dcc",
+			path.to_str().unwrap(),
+			"--format",
+			"maya",
+		];
+		let output = run_args(&args, "v 0 0 0\nv 0 0 1\n").unwrap();
+		let vertices = output_vertices(&output);
+		// Maya's y up translation (1, 2, 3) is (1, -3, 2) with z up
+		assert_near(vertices[0], Vector3::new(1.0, -3.0, 2.0));
+		// and its up axis stays up
+		assert_near(vertices[1], Vector3::new(1.0, -3.0, 3.0));
+	}
+
+	/// Obj text of a flat grid of unit quads with `size` vertices per side, vertex
+	/// (x, y) being number y * size + x + 1
+	fn quad_grid(size: usize) -> String {
+		let mut text = String::new();
+		for y in 0..size {
+			for x in 0..size {
+				text += &format!("v {} {} 0\n", x, y);
+			}
+		}
+		for y in 0..size - 1 {
+			for x in 0..size - 1 {
+				let corner = y * size + x + 1;
+				text += &format!(
+					"f {} {} {} {}\n",
+					corner,
+					corner + 1,
+					corner + size + 1,
+					corner + size
+				);
+			}
+		}
+		text
+	}
+
+	#[test]
+	fn surface_falloff_fades_linearly_over_edge_distance() {
+		let args = [
+			"surface-falloff",
+			"--seed-vertex",
+			"0",
+			"--radius",
+			"4",
+			"--offset",
+			"0,0,1",
+		];
+		let vertices = output_vertices(&run_args(&args, &quad_grid(4)).unwrap());
+		assert_eq!(vertices.len(), 16);
+		for (i, vertex) in vertices.iter().enumerate() {
+			let (x, y) = ((i % 4) as f32, (i / 4) as f32);
+			// the shortest path along the quad edges is the manhattan distance
+			let expected = (1.0 - (x + y) / 4.0).max(0.0);
+			assert_near(*vertex, Vector3::new(x, y, expected));
+		}
+		assert_near(vertices[4 + 2], Vector3::new(2.0, 1.0, 0.25));
+	}
+
+	#[test]
+	fn surface_falloff_radius_must_be_positive() {
+		for radius in ["--radius=0", "--radius=-1"] {
+			let args = [
+				"surface-falloff",
+				"--seed-vertex",
+				"0",
+				radius,
+				"--offset",
+				"0,0,1",
+			];
+			assert!(run_args(&args, &quad_grid(2)).is_err());
+		}
+	}
+
+	#[test]
+	fn set_origin_moves_the_named_vertex_to_the_origin() {
+		let input = "v 1 2 3\nv 4 6 8\nv -1 0 5\nf 1 2 3\n";
+		let output = run_args(&["set-origin", "--vertex", "1"], input).unwrap();
+		let vertices = output_vertices(&output);
+		assert_near(vertices[1], Vector3::zeros());
+		assert_near(vertices[0], Vector3::new(-3.0, -4.0, -5.0));
+		assert_near(vertices[2], Vector3::new(-5.0, -6.0, -3.0));
+		assert!(output.ends_with("f 1 2 3\n"));
+		assert!(run_args(&["set-origin", "--vertex", "3"], input).is_err());
+	}
+
+	#[test]
+	fn dual_quaternion_applies_rotation_then_translation() {
+		let real = UnitQuaternion::from_axis_angle(&Vector3::z_axis(), std::f32::consts::FRAC_PI_2);
+		let translation = Quaternion::from_imag(Vector3::new(1.0, 2.0, 3.0));
+		let dual = translation * *real.quaternion() * 0.5;
+		let values: Vec<String> = [
+			real.w, real.i, real.j, real.k, dual.w, dual.i, dual.j, dual.k,
+		]
+		.iter()
+		.map(f32::to_string)
+		.collect();
+		let motion = transformer(&["dual-quat", &values.join(",")]).unwrap();
+		assert_near(
+			motion.transform(Vector3::new(1.0, 0.0, 0.0)),
+			Vector3::new(1.0, 3.0, 3.0),
+		);
+		assert_near(
+			motion.transform(Vector3::zeros()),
+			Vector3::new(1.0, 2.0, 3.0),
+		);
+		// the real part is normalized along with the dual part
+		let doubled: Vec<String> = values
+			.iter()
+			.map(|v| (v.parse::<f32>().unwrap() * 2.0).to_string())
+			.collect();
+		let motion = transformer(&["dual-quat", &doubled.join(",")]).unwrap();
+		assert_near(
+			motion.transform(Vector3::new(1.0, 0.0, 0.0)),
+			Vector3::new(1.0, 3.0, 3.0),
+		);
+	}
+
+	#[test]
+	fn array_offsets_copies_and_reindexes_their_faces() {
+		let input = "v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n";
+		let args = ["array", "--count", "2,1,1", "--spacing", "5,0,0"];
+		let output = run_args(&args, input).unwrap();
+		let vertices = output_vertices(&output);
+		assert_eq!(vertices.len(), 6);
+		for (original, copy) in vertices[..3].iter().zip(&vertices[3..]) {
+			assert_near(*copy, original + Vector3::new(5.0, 0.0, 0.0));
+		}
+		let faces: Vec<&str> = output
+			.lines()
+			.filter(|text_line| text_line.starts_with("f "))
+			.collect();
+		assert_eq!(faces, ["f 1 2 3", "f 4 5 6"]);
+	}
+
+	#[test]
+	fn array_rejects_options_adjusting_the_transform() {
+		let input = "v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n";
+		for option in [
+			"--emit-python",
+			"--orthogonalize",
+			"--condition",
+			"--max-displacement=1",
+		] {
+			let args = ["array", "--count", "2,1,1", "--spacing", "5,0,0", option];
+			let error = run_args(&args, input).unwrap_err();
+			assert!(error.contains("cannot be used with array"), "{}", error);
+		}
+	}
+
+	#[test]
+	fn triangulate_splits_a_quad_keeping_texture_and_normal_indices() {
+		let input =
+			"v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nvt 0 0\nvt 1 0\nvt 1 1\nvt 0 1\nvn 0 0 1\n\
+		             f 1/1/1 2/2/1 3/3/1 4/4/1\nf 1/1/1 2/2/1 3/3/1\n";
+		let output = run_args(&["--triangulate", "translate", "0,0,0"], input).unwrap();
+		let faces: Vec<&str> = output
+			.lines()
+			.filter(|text_line| text_line.starts_with("f "))
+			.collect();
+		assert_eq!(
+			faces,
+			[
+				"f 1/1/1 2/2/1 3/3/1",
+				"f 1/1/1 3/3/1 4/4/1",
+				"f 1/1/1 2/2/1 3/3/1"
+			]
+		);
+		assert_eq!(
+			output
+				.lines()
+				.filter(|text_line| text_line.starts_with("vt "))
+				.count(),
+			4
+		);
+	}
+
+	/// Bumpy 6 by 6 vertex grid of quads, the second half of its faces in another
+	/// group and material
+	fn two_material_grid() -> String {
+		let mut text = String::from("mtllib grid.mtl\no grid\n");
+		for y in 0..6 {
+			for x in 0..6 {
+				text += &format!("v {} {} {}\n", x, y, 0.1 * ((x * y) % 3) as f32);
+			}
+		}
+		text += "usemtl a\n";
+		for y in 0..5 {
+			if y == 3 {
+				text += "g upper\nusemtl b\n";
+			}
+			for x in 0..5 {
+				let corner = y * 6 + x + 1;
+				text += &format!(
+					"f {} {} {} {}\n",
+					corner,
+					corner + 1,
+					corner + 7,
+					corner + 6
+				);
+			}
+		}
+		text
+	}
+
+	#[test]
+	fn simplify_reaches_the_face_ratio() {
+		let output = run_args(&["simplify", "--ratio", "0.5"], &two_material_grid()).unwrap();
+		let faces = output
+			.lines()
+			.filter(|text_line| text_line.starts_with("f "))
+			.count();
+		// 25 quads are 50 triangles
+		assert!(faces > 0 && faces <= 25, "{} faces", faces);
+		let vertices = output_vertices(&output).len();
+		for text_line in output
+			.lines()
+			.filter(|text_line| text_line.starts_with("f "))
+		{
+			let corners = &split_words(text_line, false)[1..];
+			assert!(corners
+				.iter()
+				.all(|corner| (1..=vertices).contains(&corner.parse().unwrap())));
+		}
+	}
+
+	#[test]
+	fn simplify_keeps_faces_in_their_group_and_material() {
+		let output = run_args(&["simplify", "--ratio", "0.5"], &two_material_grid()).unwrap();
+		let lines: Vec<&str> = output.lines().collect();
+		let structure: Vec<&str> = lines
+			.iter()
+			.copied()
+			.filter(|text_line| !text_line.starts_with("v ") && !text_line.starts_with("f "))
+			.collect();
+		assert_eq!(
+			structure,
+			[
+				"mtllib grid.mtl",
+				"o grid",
+				"usemtl a",
+				"g upper",
+				"usemtl b"
+			]
+		);
+		let position = |wanted: &str| {
+			lines
+				.iter()
+				.position(|text_line| *text_line == wanted)
+				.unwrap()
+		};
+		let (material_a, material_b) = (position("usemtl a"), position("usemtl b"));
+		let faces: Vec<usize> = (0..lines.len())
+			.filter(|&i| lines[i].starts_with("f "))
+			.collect();
+		assert!(lines[..material_a]
+			.iter()
+			.all(|text_line| !text_line.starts_with("f ")));
+		assert!(faces.iter().any(|&i| i > material_a && i < material_b));
+		assert!(faces.iter().any(|&i| i > material_b));
+	}
+
+	#[test]
+	fn simplify_rejects_options_adjusting_the_transform() {
+		let error = run_args(
+			&["simplify", "--ratio", "0.5", "--emit-python"],
+			&two_material_grid(),
+		);
+		assert!(error.unwrap_err().contains("cannot be used with simplify"));
+	}
+
+	#[test]
+	fn relax_area_evens_out_triangle_areas() {
+		let input = "v 0 0 0\nv 2 0 0\nv 2 2 0\nv 0 2 0\nv 0.3 0.4 0\n\
+		             f 1 2 5\nf 2 3 5\nf 3 4 5\nf 4 1 5\n";
+		let area_variance = |text: &str| {
+			let lines: Vec<String> = text.lines().map(str::to_string).collect();
+			let mesh = Mesh::parse(&lines, false);
+			let areas: Vec<f32> = mesh
+				.triangles()
+				.iter()
+				.map(|triangle| {
+					let [a, b, c] = triangle.map(|v| mesh.vertices[v]);
+					(b - a).cross(&(c - a)).norm() / 2.0
+				})
+				.collect();
+			let mean = areas.iter().sum::<f32>() / areas.len() as f32;
+			areas.iter().map(|area| (area - mean).powi(2)).sum::<f32>() / areas.len() as f32
+		};
+		let output = run_args(&["--relax-area", "5", "translate", "0,0,0"], input).unwrap();
+		assert!(area_variance(&output) < area_variance(input) / 10.0);
+		let vertices = output_vertices(&output);
+		assert_near(vertices[2], Vector3::new(2.0, 2.0, 0.0));
+		assert!((vertices[4] - Vector3::new(1.0, 1.0, 0.0)).norm() < 0.3);
+	}
+
+	#[test]
+	fn up_correct_turns_a_tilted_up_onto_z() {
+		let correct = transformer(&["up-correct", "--measured-up", "1,0,1"]).unwrap();
+		assert_near(
+			correct.transform(Vector3::new(1.0, 0.0, 1.0)),
+			Vector3::new(0.0, 0.0, 2f32.sqrt()),
+		);
+		// the rotation is about the axis perpendicular to both directions
+		assert_near(correct.transform(Vector3::y()), Vector3::y());
+	}
+
+	#[test]
+	fn up_correct_turns_an_upside_down_scan_over() {
+		let correct = transformer(&["up-correct", "--measured-up", "0,0,-1"]).unwrap();
+		assert_near(
+			correct.transform(Vector3::new(0.0, 0.0, -2.0)),
+			Vector3::new(0.0, 0.0, 2.0),
+		);
+		let rotation = correct
+			.affine()
+			.unwrap()
+			.fixed_view::<3, 3>(0, 0)
+			.into_owned();
+		assert!((rotation.determinant() - 1.0).abs() < 1e-5);
+		assert!(
+			(rotation * rotation.transpose() - Matrix3::identity())
+				.abs()
+				.max() < 1e-5
+		);
+	}
+
+	#[test]
+	fn symmetry_finds_the_mid_planes_of_a_box() {
+		let corners = "v 0 0 0\nv 2 0 0\nv 0 4 0\nv 2 4 0\nv 0 0 6\nv 2 0 6\nv 0 4 6\nv 2 4 6\n";
+		let output = run_args(&["symmetry", "--plane", "1,2,0"], corners).unwrap();
+		let lines: Vec<&str> = output.lines().collect();
+		assert_eq!(lines[0], "center 1 2 3");
+		let verdict = |normal: &str| {
+			let prefix = format!("normal {} ", normal);
+			let found = lines
+				.iter()
+				.find(|text_line| text_line.starts_with(&prefix))
+				.unwrap();
+			found.ends_with(" symmetric")
+		};
+		for normal in ["1,0,0", "0,1,0", "0,0,1"] {
+			assert!(verdict(normal), "{}", normal);
+		}
+		// the box is not a cube, so no diagonal plane mirrors it
+		for normal in [
+			"1,1,0", "1,-1,0", "0,1,1", "0,1,-1", "1,0,1", "1,0,-1", "1,2,0",
+		] {
+			assert!(!verdict(normal), "{}", normal);
+		}
+	}
+
+	#[test]
+	fn symmetry_rejects_a_plane_one_vertex_breaks() {
+		let lopsided = "v 0 0 0\nv 2 0 0\nv 0 4 0\nv 2 4 0\nv 0.5 1 1\n";
+		let output = run_args(&["symmetry"], lopsided).unwrap();
+		assert!(
+			output.contains("normal 1,0,0 matched 80.0% asymmetric"),
+			"{}",
+			output
+		);
+		assert!(
+			output.contains("normal 0,1,0 matched 80.0% asymmetric"),
+			"{}",
+			output
+		);
+	}
+
+	#[test]
+	fn units_scale_by_the_ratio_of_their_lengths() {
+		let scale = |from: &str, to: &str| {
+			let units = transformer(&["units", "--from", from, "--to", to]).unwrap();
+			units.transform(Vector3::new(1.0, 1.0, 1.0)).x
+		};
+		assert_eq!(scale("mm", "m"), 0.001);
+		assert_eq!(scale("inch", "mm"), 25.4);
+		assert_eq!(scale("in", "cm"), 2.54);
+		assert_eq!(scale("ft", "inch"), 12.0);
+		assert_eq!(scale("0.5", "m"), 0.5);
+		assert!(parse_unit("0").is_err());
+		assert!(parse_unit("furlong").is_err());
+	}
+
+	#[test]
+	fn outline_of_a_raised_box_is_a_flat_square() {
+		let mut corners = String::new();
+		for x in [0, 2] {
+			for y in [0, 2] {
+				for z in [3, 5] {
+					corners += &format!("v {} {} {}\n", x, y, z);
+				}
+			}
+		}
+		let output = run_args(&["footprint", "--outline"], &corners).unwrap();
+		let vertices = output_vertices(&output);
+		assert_eq!(vertices.len(), 4);
+		assert!(vertices.iter().all(|v| v.z == 0.0));
+		for corner in [(0.0, 0.0), (2.0, 0.0), (2.0, 2.0), (0.0, 2.0)] {
+			let corner = Vector3::new(corner.0, corner.1, 0.0);
+			assert!(
+				vertices.iter().any(|&v| (v - corner).norm() < 1e-5),
+				"{}",
+				corner
+			);
+		}
+		assert!(output.ends_with("f 1 2 3 4\n"));
+		let error = run_args(
+			&["footprint", "--outline", "--max-displacement=1"],
+			&corners,
+		);
+		assert!(error
+			.unwrap_err()
+			.contains("cannot be used with footprint --outline"));
+	}
+
+	/// Records how many lines had been written each time it was flushed
+	#[derive(Default)]
+	struct FlushRecorder {
+		written: Vec<u8>,
+		flushed_lines: Vec<usize>,
+	}
+
+	impl Write for FlushRecorder {
+		fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+			self.written.extend_from_slice(buf);
+			Ok(buf.len())
+		}
+
+		fn flush(&mut self) -> io::Result<()> {
+			let lines = self.written.iter().filter(|&&b| b == b'\n').count();
+			if self.flushed_lines.last() != Some(&lines) {
+				self.flushed_lines.push(lines);
+			}
+			Ok(())
+		}
+	}
+
+	#[test]
+	fn chunk_lines_flushes_after_every_chunk() {
+		let args =
+			Args::try_parse_from(["mesh_transform", "--chunk-lines", "2", "translate", "1,0,0"])
+				.unwrap();
+		let input = "v 0 0 0\nv 1 0 0\nv 2 0 0\nv 3 0 0\nv 4 0 0\n";
+		let mut out = FlushRecorder::default();
+		run(args.command, &args.options, input.as_bytes(), &mut out).unwrap();
+		assert_eq!(out.flushed_lines, [2, 4, 5]);
+	}
+
+	#[test]
+	fn chunk_lines_is_rejected_before_reading_input() {
+		let whole_input: [&[&str]; 2] = [
+			&["--chunk-lines", "2", "set-origin", "--vertex", "0"],
+			&[
+				"--chunk-lines",
+				"2",
+				"--relax-area",
+				"1",
+				"translate",
+				"0,0,0",
+			],
+		];
+		for args in whole_input {
+			let args = Args::try_parse_from(["mesh_transform"].iter().chain(args)).unwrap();
+			let mut input = "v 0 0 0\n".as_bytes();
+			let error = run(args.command, &args.options, &mut input, Vec::new()).unwrap_err();
+			assert!(error.contains("--chunk-lines"), "{}", error);
+			assert_eq!(input, b"v 0 0 0\n");
+		}
+	}
+
+	#[test]
+	fn by_color_transforms_each_color_group_by_its_entry() {
+		let manifest = temp_file(
+			"colors.txt",
+			"1,0,0 translate 10,0,0\n0,0,1 translate 0,0,10\n",
+		);
+		let input = "v 0 0 0 1 0 0\nv 1 0 0 0 0 1\nv 2 0 0\nv 3 0 0 1 0 0.0005\n";
+		let by_color = ["--by-color", manifest.to_str().unwrap()];
+		let output = run_args(&[&by_color[..], &["translate", "0,1,0"]].concat(), input).unwrap();
+		assert_eq!(
+			output,
+			"v 10 0 0 1 0 0\nv 1 0 10 0 0 1\nv 2 1 0\nv 13 0 0 1 0 0.0005\n"
+		);
+		// options wrapping the transform still see the colors, one chunk at a time
+		let clamped = [
+			&by_color[..],
+			&[
+				"--max-displacement=1",
+				"--chunk-lines",
+				"1",
+				"translate",
+				"0,1,0",
+			],
+		];
+		let output = run_args(&clamped.concat(), input).unwrap();
+		assert_eq!(
+			output,
+			"v 1 0 0 1 0 0\nv 1 0 1 0 0 1\nv 2 1 0\nv 4 0 0 1 0 0.0005\n"
+		);
+	}
+
+	#[test]
+	fn snap_to_planes_picks_the_middle_of_distinct_values() {
+		let input = "v 0 0 0.001\nv 5 0 -0.001\nv 0 5 0\nv 5 5 0.0005\n";
+		let output = run_args(
+			&["snap-to-planes", "--eps", "0.002", "--min-vertices", "3"],
+			input,
+		)
+		.unwrap();
+		let vertices = output_vertices(&output);
+		assert_eq!(vertices.len(), 4);
+		assert!(vertices.iter().all(|v| v.z == 0.0), "{:?}", vertices);
+	}
+
+	#[test]
+	fn snap_to_planes_prefers_the_most_repeated_value() {
+		assert_eq!(SnapTransformer::most_common(&[0.0, 0.25, 0.25, 0.3]), 0.25);
+		assert_eq!(
+			SnapTransformer::most_common(&[1.0, 1.0, 2.0, 2.0, 2.5]),
+			2.0
+		);
+	}
+
+	fn base64_decode(text: &str) -> Vec<u8> {
+		let alphabet = Base64Writer::<Vec<u8>>::ALPHABET;
+		let mut bytes = Vec::new();
+		for group in text.as_bytes().chunks(4) {
+			let values: Vec<u32> = group
+				.iter()
+				.take_while(|&&c| c != b'=')
+				.map(|c| alphabet.iter().position(|a| a == c).unwrap() as u32)
+				.collect();
+			let bits =
+				values.iter().fold(0, |bits, value| bits << 6 | value) << (6 * (4 - values.len()));
+			bytes.extend((0..values.len() - 1).map(|i| (bits >> (16 - 8 * i)) as u8));
+		}
+		bytes
+	}
+
+	#[test]
+	fn base64_output_decodes_back_to_the_plain_output() {
+		let mut encoded = Vec::new();
+		let mut writer = Base64Writer::new(&mut encoded);
+		writer.write_all(b"Man Ma M").unwrap();
+		writer.finish().unwrap();
+		assert_eq!(encoded, b"TWFuIE1hIE0=");
+
+		for input in [
+			"v 1 2 3\n",
+			"v 1 2 3\nv 4 5 6\n",
+			"v 1 2 3\nf 1 1 1\n# end\n",
+		] {
+			let plain = run_args(&["translate", "0.5,0,0"], input).unwrap();
+			let encoded = run_args(&["--base64", "translate", "0.5,0,0"], input).unwrap();
+			assert_eq!(encoded.len() % 4, 0);
+			assert_eq!(String::from_utf8(base64_decode(&encoded)).unwrap(), plain);
+		}
+	}
+
+	#[test]
+	fn curvature_displace_moves_the_ridge_most() {
+		// a roof over a 7 by 7 grid with its ridge along x = 3
+		let roof: Vec<String> = quad_grid(7)
+			.lines()
+			.map(
+				|text_line| match parse_vertex(&split_words(text_line, false)) {
+					Some(v) => format!("v {} {} {}", v.x, v.y, -0.5 * (v.x - 3.0).abs()),
+					None => text_line.to_string(),
+				},
+			)
+			.collect();
+		let input = roof.join("\n") + "\n";
+		let before = output_vertices(&input);
+		let output = run_args(&["curvature-displace", "--amount", "1"], &input).unwrap();
+		let moved: Vec<(Vector3<f32>, f32)> = before
+			.iter()
+			.zip(output_vertices(&output))
+			.map(|(&b, a)| (b, (a - b).norm()))
+			.collect();
+		let ridge = |v: &Vector3<f32>| v.x == 3.0 && v.y > 0.0 && v.y < 6.0;
+		let least_on_ridge = moved
+			.iter()
+			.filter(|(v, _)| ridge(v))
+			.map(|&(_, d)| d)
+			.fold(f32::INFINITY, f32::min);
+		let most_elsewhere = moved
+			.iter()
+			.filter(|(v, _)| !ridge(v))
+			.map(|&(_, d)| d)
+			.fold(0.0, f32::max);
+		assert!(least_on_ridge > 0.0);
+		assert!(
+			least_on_ridge > most_elsewhere,
+			"{} <= {}",
+			least_on_ridge,
+			most_elsewhere
+		);
+	}
+
+	#[test]
+	fn warp_applies_pre_before_and_post_after() {
+		let args = [
+			"warp",
+			"--line",
+			"0,0,0 1,0,0",
+			"--line",
+			"0,0,0 1,1,0",
+			"--pre",
+			"scale 2,1,1",
+			"--post",
+			"translate 1,0,0",
+		];
+		let warp = transformer(&args).unwrap();
+		let plain = WarpTransformer::new(vec![
+			parse_line("0,0,0 1,0,0", false).unwrap(),
+			parse_line("0,0,0 1,1,0", false).unwrap(),
+		]);
+		let (pre, post) = (Vector3::new(2.0, 1.0, 1.0), Vector3::new(1.0, 0.0, 0.0));
+		for pt in [Vector3::new(1.0, 0.5, 0.0), Vector3::new(0.0, 1.0, 2.0)] {
+			let expected = plain.transform(pt.component_mul(&pre)) + post;
+			assert_near(warp.transform(pt), expected);
+			// the warp moves points, so the order around it matters
+			let reordered = plain.transform(pt).component_mul(&pre) + post;
+			assert!((expected - reordered).norm() > 0.1);
+		}
+
+		let non_affine = [
+			"warp",
+			"--pre",
+			"wave --direction 1,0,0 --amplitude 1 --wavelength 1",
+		];
+		assert!(transformer(&non_affine).is_err());
+	}
+
+	#[test]
+	fn lines_file_warps_like_the_same_lines_given_inline() {
+		let path = temp_file(
+			"warp_lines.txt",
+			"# origin heading\n0,0,0 1,0,0\n\n0,0,1 0,1,1.5\n2,0,0 2.5,1,0\n",
+		);
+		let inline = [
+			"warp",
+			"--line",
+			"0,0,0 1,0,0",
+			"--line",
+			"0,0,1 0,1,1.5",
+			"--line",
+			"2,0,0 2.5,1,0",
+		];
+		let from_file = ["warp", "--lines-file", path.to_str().unwrap()];
+		let input = "v 1 2 3\nv -1 0.5 0\nv 0.3 -2 1\n";
+		assert_eq!(
+			run_args(&from_file, input).unwrap(),
+			run_args(&inline, input).unwrap()
+		);
+		assert_ne!(run_args(&from_file, input).unwrap(), input);
+
+		let broken = temp_file("broken_warp_lines.txt", "0,0,0 1,0,0\n0,0,1\n");
+		let error =
+			run_args(&["warp", "--lines-file", broken.to_str().unwrap()], input).unwrap_err();
+		assert!(error.ends_with("on line 2"), "{}", error);
+	}
+
+	#[test]
+	fn scale_oriented_stretches_only_along_the_rotated_axis() {
+		let angle = std::f32::consts::FRAC_PI_4.to_string();
+		let args = [
+			"scale-oriented",
+			"--axis",
+			"0,0,1",
+			"--angle",
+			&angle,
+			"--scale",
+			"3,1,1",
+		];
+		let scale = transformer(&args).unwrap();
+		let diagonal = Vector3::new(1.0, 1.0, 0.0);
+		assert_near(scale.transform(diagonal), diagonal * 3.0);
+		for perpendicular in [Vector3::new(-1.0, 1.0, 0.0), Vector3::new(0.0, 0.0, 2.0)] {
+			assert_near(scale.transform(perpendicular), perpendicular);
+		}
+	}
+
+	#[test]
+	fn condition_number_flags_near_singular_transforms() {
+		let condition =
+			|args: &[&str]| condition_number(&transformer(args).unwrap().affine().unwrap());
+		let flattening = condition(&["scale", "1,1,0.0001"]);
+		assert!((flattening - 1e4).abs() < 1.0, "{}", flattening);
+		assert!(flattening > CONDITION_WARNING);
+		let rotation = condition(&["rotate", "1,2,3", "0.7"]);
+		assert!((rotation - 1.0).abs() < 1e-4, "{}", rotation);
+		// translation does not affect the condition
+		assert_eq!(condition(&["translate", "100,0,0"]), 1.0);
+	}
+
+	#[test]
+	fn sphere_normalize_puts_the_farthest_vertex_on_the_radius() {
+		let input = "v 1 1 1\nv 3 1 1\nv 1 5 1\nv 3 5 1\n";
+		for center in ["centroid", "box", "sphere"] {
+			let output = run_args(
+				&["sphere-normalize", "--radius", "2", "--center", center],
+				input,
+			)
+			.unwrap();
+			let vertices = output_vertices(&output);
+			let farthest = vertices.iter().map(|v| v.norm()).fold(0.0, f32::max);
+			assert!((farthest - 2.0).abs() < 1e-5, "{}", farthest);
+			assert_near(
+				vertices.iter().sum::<Vector3<f32>>() / 4.0,
+				Vector3::zeros(),
+			);
+		}
+		let lopsided = "v 0 0 0\nv 0 0 0\nv 0 0 0\nv 4 0 0\n";
+		let output = run_args(&["sphere-normalize", "--center", "centroid"], lopsided).unwrap();
+		// the centroid is at x = 1, so the lone vertex is 3 away and the others 1
+		assert_near(output_vertices(&output)[3], Vector3::new(1.0, 0.0, 0.0));
+		assert_near(
+			output_vertices(&output)[0],
+			Vector3::new(-1.0 / 3.0, 0.0, 0.0),
+		);
+		// the enclosing circle of an acute triangle passes through all three corners
+		let acute = "v 0 0 0\nv 4 0 0\nv 2 3 0\n";
+		let output = run_args(&["sphere-normalize", "--center", "sphere"], acute).unwrap();
+		for vertex in output_vertices(&output) {
+			assert!((vertex.norm() - 1.0).abs() < 1e-5, "{}", vertex);
+		}
+		let output = run_args(&["sphere-normalize", "--center", "box"], acute).unwrap();
+		assert_near(output_vertices(&output)[2], Vector3::new(0.0, 0.6, 0.0));
+	}
+
+	#[test]
+	fn largest_component_only_leaves_floaters_in_place() {
+		let input = "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nv 5 5 5\nv 6 5 5\nv 5 6 5\n\
+		             f 1 2 3\nf 1 3 4\nf 5 6 7\n";
+		let output = run_args(&["--largest-component-only", "translate", "0,0,1"], input).unwrap();
+		let vertices = output_vertices(&output);
+		let before = output_vertices(input);
+		for (i, (after, before)) in vertices.iter().zip(&before).enumerate() {
+			let expected = if i < 4 {
+				before + Vector3::z()
+			} else {
+				*before
+			};
+			assert_near(*after, expected);
+		}
+	}
+
+	#[test]
+	fn tps_maps_control_points_exactly_and_interpolates_smoothly() {
+		let from = temp_file("tps_from.txt", "0 0 0\n1 0 0\n0 1 0\n0 0 1\n1 1 1\n");
+		let tps = |to: &Path| {
+			let args = [
+				"tps",
+				"--from-points",
+				from.to_str().unwrap(),
+				"--to-points",
+				to.to_str().unwrap(),
+			];
+			transformer(&args).unwrap()
+		};
+		let to = temp_file(
+			"tps_to.txt",
+			"v 0 0 0\nv 1 0 0.2\nv 0 1 0\nv 0 0 1\nv 1.2 1 1.3\n",
+		);
+		let warp = tps(&to);
+		let sources = read_points_file(&from, false).unwrap();
+		for (source, target) in sources
+			.into_iter()
+			.zip(read_points_file(&to, false).unwrap())
+		{
+			assert_near(warp.transform(source), target);
+		}
+		// nearby interior points stay nearby
+		let inside = Vector3::new(0.4, 0.3, 0.35);
+		let step = Vector3::new(1e-3, -1e-3, 1e-3);
+		assert!((warp.transform(inside + step) - warp.transform(inside)).norm() < 1e-2);
+
+		// control points moved by an affine transform move everything by it
+		let shifted = temp_file("tps_affine.txt", "1 2 3\n3 2 3\n1 4 3\n1 2 5\n3 4 5\n");
+		assert_near(
+			tps(&shifted).transform(inside),
+			inside * 2.0 + Vector3::new(1.0, 2.0, 3.0),
+		);
+	}
+
+	#[test]
+	fn sag_is_deepest_midway_with_the_ends_pinned() {
+		let input: String = (0..=4).map(|x| format!("v {} 0 0\n", x)).collect();
+		let output = run_args(&["sag", "--pinned-axis", "x", "--strength", "1"], &input).unwrap();
+		let depths: Vec<f32> = output_vertices(&output).iter().map(|v| -v.z).collect();
+		assert_eq!((depths[0], depths[4]), (0.0, 0.0));
+		assert!((depths[2] - 1.0).abs() < 1e-5, "{:?}", depths);
+		assert!(depths[1] > 0.0 && depths[1] < depths[2]);
+		assert!((depths[1] - depths[3]).abs() < 1e-5);
+		assert!(output_vertices(&output).iter().all(|v| v.y == 0.0));
+	}
+
+	#[test]
+	fn jacobian_of_an_identity_warp_is_identity() {
+		let args = [
+			"warp",
+			"--line",
+			"0,0,0 1,0,0",
+			"--line",
+			"0,0,0 1,0,0",
+			"--warp-jacobian",
+			"1,2,3",
+			"--warp-jacobian",
+			"-4,0.5,2",
+		];
+		let output = run_args(&args, "v 0 0 0\n").unwrap();
+		let lines: Vec<&str> = output.lines().collect();
+		assert_eq!(lines.len(), 10);
+		assert_eq!(
+			(lines[0], lines[5]),
+			("jacobian at 1,2,3", "jacobian at -4,0.5,2")
+		);
+		let numbers = output_numbers(&output);
+		for report in [&numbers[1..5], &numbers[6..10]] {
+			let rows: Vec<f32> = report[..3].concat();
+			let jacobian = Matrix3::from_row_slice(&rows);
+			assert!(
+				(jacobian - Matrix3::identity()).abs().max() < 1e-4,
+				"{}",
+				jacobian
+			);
+			assert!((report[3][0] - 1.0).abs() < 1e-4);
+		}
+	}
+
+	#[test]
+	fn lenient_parse_accepts_commas_spaces_or_both() {
+		let input = "v 1 2 3\nv 1,2,3\nv 1, 2, 3\n";
+		for vector in ["1, 2, 3", "1,2,3", "1 2 3"] {
+			let output = run_args(&["--lenient-parse", "translate", vector], input).unwrap();
+			assert_eq!(output, "v 2 4 6\nv 2 4 6\nv 2 4 6\n", "{}", vector);
+		}
+		for vector in ["1, 2, 3", "1 2 3"] {
+			assert!(run_args(&["translate", vector], "v 0 0 0\n").is_err());
+		}
+		let strict = run_args(&["translate", "1,2,3"], input).unwrap();
+		assert_eq!(strict, "v 2 4 6\nv 1,2,3\nv 1, 2, 3\n");
+	}
+
+	#[test]
+	fn fake_depth_pulls_farther_vertices_further_inward() {
+		let depth =
+			transformer(&["fake-depth", "--camera", "0,0,10", "--strength", "0.1"]).unwrap();
+		let lateral = |pt: Vector3<f32>| depth.transform(pt).xy().norm();
+		let (near, far) = (Vector3::new(1.0, 0.0, 0.0), Vector3::new(1.0, 0.0, -10.0));
+		assert!(lateral(far) < lateral(near));
+		assert!(lateral(near) < 1.0);
+		// depth along the view axis is kept
+		assert_eq!(depth.transform(far).z, -10.0);
+		assert_near(
+			depth.transform(Vector3::new(0.0, 0.0, -3.0)),
+			Vector3::new(0.0, 0.0, -3.0),
+		);
+	}
+
+	#[test]
+	fn emit_python_writes_the_matrix() {
+		let output = run_args(&["--emit-python", "translate", "1,2,3"], "v 0 0 0\n").unwrap();
+		assert!(output.starts_with("import numpy as np"));
+		assert!(output.contains("    [1.0, 0.0, 0.0, 1.0],\n    [0.0, 1.0, 0.0, 2.0],\n    [0.0, 0.0, 1.0, 3.0],\n    [0.0, 0.0, 0.0, 1.0],"));
+		assert!(!output.contains("v 0 0 0"));
+		let error = run_args(
+			&[
+				"--emit-python",
+				"fake-depth",
+				"--camera",
+				"0,0,10",
+				"--strength",
+				"1",
+			],
+			"",
+		)
+		.unwrap_err();
+		assert!(error.contains("only supports affine transforms"));
+	}
+
+	#[test]
+	fn ease_writes_one_eased_frame_per_step() {
+		let pattern = temp_file("ease_{}.obj", "");
+		std::fs::remove_file(&pattern).unwrap();
+		let pattern = pattern.to_str().unwrap();
+		let args = [
+			"ease",
+			"--transform",
+			"translate 10,0,0",
+			"--steps",
+			"5",
+			"--curve",
+			"easeInOut",
+			"--output",
+			pattern,
+		];
+		assert_eq!(run_args(&args, "v 0 0 0\n").unwrap(), "");
+		for (frame, x) in [0.0, 1.25, 5.0, 8.75, 10.0].into_iter().enumerate() {
+			let path = pattern.replace("{}", &frame.to_string());
+			let vertices = output_vertices(&std::fs::read_to_string(&path).unwrap());
+			std::fs::remove_file(path).unwrap();
+			assert_eq!(vertices.len(), 1);
+			assert_near(vertices[0], Vector3::new(x, 0.0, 0.0));
+		}
+
+		let error = run_args(
+			&["--max-displacement", "1", args[0], args[1], args[2]],
+			"v 0 0 0\n",
+		)
+		.unwrap_err();
+		assert_eq!(error, "--max-displacement cannot be used with ease.");
+		let error = run_args(
+			&["--chunk-lines", "2", args[0], args[1], args[2]],
+			"v 0 0 0\n",
+		)
+		.unwrap_err();
+		assert_eq!(
+			error,
+			"--chunk-lines cannot be used with commands that read the whole input."
+		);
+	}
+
+	#[test]
+	fn octahedral_normals_keep_axes_and_bound_the_error() {
+		let input = "vn 0 0 1\nvn 0 -1 0\nvn 1 0 0\nvn 0 0 -1\n";
+		let output = run_args(&["--octahedral-normals", "8", "translate", "0,0,0"], input).unwrap();
+		assert_eq!(output, "vn 0 0 1\nvn 0 -1 0\nvn 1 0 0\nvn 0 0 -1\n");
+
+		let mut seed = 1u32;
+		let mut random = || {
+			seed = seed.wrapping_mul(1664525).wrapping_add(1013904223);
+			(seed >> 8) as f32 / (1 << 24) as f32 * 2.0 - 1.0
+		};
+		for _ in 0..1000 {
+			let normal = Vector3::new(random(), random(), random());
+			if normal.norm() < 0.01 {
+				continue;
+			}
+			let normal = normal.normalize();
+			for (bits, bound) in [(8, 0.02), (16, 1e-4)] {
+				let decoded = octahedral_round_trip(normal, bits);
+				assert!((decoded.norm() - 1.0).abs() < 1e-5);
+				assert!(
+					(decoded - normal).norm() < bound,
+					"{normal} became {decoded} with {bits} bits"
+				);
+			}
+		}
+	}
+
+	#[test]
+	fn apply_frames_uses_the_latest_frame_section() {
+		let input = "v 1 1 1\n\
+			# frame 1 0 0 10 0 1 0 0 0 0 1 0 0 0 0 1\n\
+			v 0 0 0\n\
+			v 0 1 0\n\
+			# frame ((2, 0, 0, 0), (0, 2, 0, 0), (0, 0, 2, 0), (0, 0, 0, 1))\n\
+			v 1 1 1\n";
+		let output = run_args(&["--apply-frames", "translate", "1,0,0"], input).unwrap();
+		let expected = [
+			(2.0, 1.0, 1.0),
+			(11.0, 0.0, 0.0),
+			(11.0, 1.0, 0.0),
+			(3.0, 2.0, 2.0),
+		];
+		let vertices = output_vertices(&output);
+		assert_eq!(vertices.len(), expected.len());
+		for (vertex, (x, y, z)) in vertices.into_iter().zip(expected) {
+			assert_near(vertex, Vector3::new(x, y, z));
+		}
+
+		let output = run_args(&["translate", "1,0,0"], input).unwrap();
+		assert!(output.contains("# frame 1 0 0 10"));
+		assert_near(output_vertices(&output)[1], Vector3::new(1.0, 0.0, 0.0));
+	}
+
+	#[test]
+	fn profile_summary_reports_sorted_percentiles() {
+		let mut samples: Vec<Duration> = (1..=100).rev().map(Duration::from_micros).collect();
+		assert_eq!(
+			profile_summary(&mut samples),
+			"profile: 100 sampled vertices, mean 50.500us, min 1.000us, median 50.000us, p90 90.000us, p99 99.000us, max 100.000us"
+		);
+		assert_eq!(profile_summary(&mut []), "profile: no vertices transformed");
+
+		let profiled = ProfiledTransformer {
+			inner: transformer(&["translate", "1,0,0"]).unwrap(),
+			vertices: Cell::new(0),
+			samples: Rc::new(RefCell::new(Vec::new())),
+		};
+		for i in 0..2 * PROFILE_STRIDE + 1 {
+			assert_near(profiled.transform_vertex(i, Vector3::zeros()), Vector3::x());
+		}
+		assert_eq!(profiled.samples.borrow().len(), 3);
+	}
+
+	#[test]
+	fn frame_align_turns_principal_axes_onto_the_frame() {
+		let rotation = Rotation3::from_axis_angle(&Vector3::z_axis(), std::f32::consts::FRAC_PI_4);
+		let centre = Vector3::new(5.0, 5.0, 5.0);
+		let mut input = String::new();
+		for corner in 0..8 {
+			let sign = |bit: i32| if corner >> bit & 1 == 0 { -1.0 } else { 1.0 };
+			let pt = centre + rotation * Vector3::new(3.0 * sign(0), 1.5 * sign(1), 0.5 * sign(2));
+			input += &format!("v {} {} {}\n", pt.x, pt.y, pt.z);
+		}
+		let output = run_args(&["frame-align", "--x", "0,0,1", "--y", "1,0,0"], &input).unwrap();
+		let vertices = output_vertices(&output);
+		assert_eq!(vertices.len(), 8);
+		let (min, max) = vertices.iter().fold(
+			(Vector3::repeat(f32::MAX), Vector3::repeat(f32::MIN)),
+			|(min, max), v| (min.inf(v), max.sup(v)),
+		);
+		// the longest side now runs along z and the second longest along x
+		assert_near(max - min, Vector3::new(3.0, 1.0, 6.0));
+		assert_near((max + min) / 2.0, centre);
+	}
+
+	#[test]
+	fn bounding_sphere_encloses_every_vertex_and_touches_at_least_two() {
+		let mut seed = 7u32;
+		let mut random = || {
+			seed = seed.wrapping_mul(1664525).wrapping_add(1013904223);
+			(seed >> 8) as f32 / (1 << 24) as f32 * 4.0 - 2.0
+		};
+		let mut input = String::new();
+		for _ in 0..200 {
+			input += &format!("v {} {} {}\n", random(), 0.5 * random(), random() + 3.0);
+		}
+		let output = run_args(&["bounding-sphere", "--normalize"], &input).unwrap();
+		let distances: Vec<f32> = output_vertices(&output).iter().map(|v| v.norm()).collect();
+		assert_eq!(distances.len(), 200);
+		assert!(distances.iter().all(|&d| d <= 1.0 + 1e-4));
+		assert!(distances.iter().filter(|&&d| d >= 1.0 - 1e-4).count() >= 2);
+
+		let output = run_args(&["bounding-sphere"], "v -1 0 0\nv 3 0 0\nv 1 1 0\n").unwrap();
+		assert_eq!(output, "center 1 0 0\nradius 2\n");
+	}
+
+	#[test]
+	fn snap_to_planes_rejects_a_distance_that_is_not_positive() {
+		for eps in ["--eps=-1", "--eps=0", "--eps=NaN"] {
+			let error = run_args(&["snap-to-planes", eps], "v 0 0 0\nv 1 1 1\n").unwrap_err();
+			assert_eq!(error, "The snapping distance must be positive.");
+		}
+	}
+
+	#[test]
+	fn apply_frames_is_rejected_with_commands_reading_the_whole_input() {
+		let input = "# frame 1 0 0 100 0 1 0 0 0 0 1 0 0 0 0 1\nv -1 0 0\nv 1 0 0\n";
+		for args in [
+			&["--apply-frames", "sphere-normalize"][..],
+			&["--apply-frames", "--relax-area", "1", "translate", "0,0,0"],
+			&[
+				"--apply-frames",
+				"--largest-component-only",
+				"translate",
+				"0,0,0",
+			],
+		] {
+			assert_eq!(
+				run_args(args, input).unwrap_err(),
+				"--apply-frames cannot be used with commands that read the whole input."
+			);
+		}
+	}
+
+	#[test]
+	fn simplify_rejects_a_ratio_outside_zero_to_one() {
+		for ratio in ["--ratio=-1", "--ratio=0", "--ratio=NaN", "--ratio=5"] {
+			let error =
+				run_args(&["simplify", ratio], "v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n").unwrap_err();
+			assert_eq!(error, "The ratio must be greater than 0 and at most 1.");
+		}
+	}
+
+	#[test]
+	fn array_rejects_a_zero_step_axis() {
+		let error = run_args(
+			&[
+				"array",
+				"--count",
+				"2,1,1",
+				"--spacing",
+				"1,0,0",
+				"--step-axis",
+				"0,0,0",
+			],
+			"v 0 0 0\n",
+		)
+		.unwrap_err();
+		assert_eq!(error, "The step axis must not be zero.");
+	}
+
+	#[test]
+	fn scale_oriented_rejects_a_zero_axis() {
+		let error = transformer(&[
+			"scale-oriented",
+			"--axis",
+			"0,0,0",
+			"--angle",
+			"1",
+			"--scale",
+			"2,1,1",
+		])
+		.err();
+		assert_eq!(error.as_deref(), Some("The frame axis must not be zero."));
+	}
+
+	#[test]
+	fn fake_depth_rejects_a_negative_strength() {
+		for strength in ["--strength=-0.1", "--strength=NaN"] {
+			let error = transformer(&["fake-depth", "--camera", "0,0,10", strength]).err();
+			assert_eq!(error.as_deref(), Some("The strength must not be negative."));
+		}
+	}
+
+	#[test]
+	fn edge_align_rejects_a_zero_target() {
+		let error = run_args(
+			&["edge-align", "--to", "0,0,0"],
+			"v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n",
+		)
+		.unwrap_err();
+		assert_eq!(error, "The target direction must not be zero.");
+	}
+
+	#[test]
+	fn histogram_rejects_transform_options() {
+		let error = run_args(
+			&["--max-displacement", "1", "histogram", "--axis", "x"],
+			"v 0 0 0\n",
+		)
+		.unwrap_err();
+		assert_eq!(error, "--max-displacement cannot be used with histogram.");
+	}
+
+	#[test]
+	fn symmetry_rejects_transform_options() {
+		let error = run_args(&["--orthogonalize", "symmetry"], "v 0 0 0\n").unwrap_err();
+		assert_eq!(error, "--orthogonalize cannot be used with symmetry.");
+	}
+
+	#[test]
+	fn warp_jacobian_rejects_transform_options() {
+		let args = ["--condition", "warp", "--warp-jacobian", "1,2,3"];
+		let error = run_args(&args, "v 0 0 0\n").unwrap_err();
+		assert_eq!(error, "--condition cannot be used with --warp-jacobian.");
+	}
+
+	#[test]
+	fn bounding_sphere_report_rejects_transform_options() {
+		let error = run_args(&["--emit-python", "bounding-sphere"], "v 0 0 0\n").unwrap_err();
+		assert_eq!(error, "--emit-python cannot be used with bounding-sphere.");
+		let output = run_args(
+			&["--emit-python", "bounding-sphere", "--normalize"],
+			"v 0 0 0\nv 2 0 0\n",
+		)
+		.unwrap();
+		assert!(output.contains("[1.0, 0.0, 0.0, -1.0],"));
+	}
+
+	#[test]
+	fn footprint_outline_rejects_a_degenerate_hull() {
+		for input in ["", "v 0 0 0\nv 1 1 5\nv 2 2 1\n"] {
+			let error = run_args(&["footprint", "--outline"], input).unwrap_err();
+			assert_eq!(error, "The outline needs vertices spanning an area.");
+		}
+	}
+
+	#[test]
+	fn ease_moves_part_way_along_a_non_affine_transform() {
+		let pattern = temp_file("ease_lerp_{}.obj", "");
+		std::fs::remove_file(&pattern).unwrap();
+		let pattern = pattern.to_str().unwrap();
+		let target = "fake-depth --camera 0,0,10 --strength 1";
+		let args = [
+			"ease",
+			"--transform",
+			target,
+			"--steps",
+			"3",
+			"--output",
+			pattern,
+		];
+		run_args(&args, "v 1 0 0\n").unwrap();
+		let start = Vector3::new(1.0, 0.0, 0.0);
+		let end = parse_transform(target, false).unwrap().transform(start);
+		for (frame, t) in [0.0, 0.5, 1.0].into_iter().enumerate() {
+			let path = pattern.replace("{}", &frame.to_string());
+			let vertices = output_vertices(&std::fs::read_to_string(&path).unwrap());
+			std::fs::remove_file(path).unwrap();
+			assert_near(vertices[0], start.lerp(&end, t));
+		}
 	}
 }